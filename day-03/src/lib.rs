@@ -0,0 +1,288 @@
+use std::{collections::HashSet, fmt, ops::RangeInclusive};
+
+use unicode_segmentation::UnicodeSegmentation;
+
+// graphemes that are neither digits nor symbols
+const BLANK: &str = ".";
+
+// the alphabet of graphemes that are treated as symbols in a schematic
+#[derive(Debug, Clone, Default)]
+pub struct SymbolSet {
+    // `None` means that every grapheme that is neither a blank nor a digit is a symbol
+    symbols: Option<HashSet<String>>,
+}
+
+impl SymbolSet {
+    pub fn any() -> Self {
+        Self::default()
+    }
+
+    pub fn only<I, S>(symbols: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            symbols: Some(symbols.into_iter().map(Into::into).collect()),
+        }
+    }
+
+    pub fn contains(&self, grapheme: &str) -> bool {
+        match &self.symbols {
+            Some(symbols) => symbols.contains(grapheme),
+            None => grapheme != BLANK && digit_value(grapheme).is_none(),
+        }
+    }
+}
+
+// a number in the schematic; columns are counted in graphemes, not in bytes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartNumber {
+    pub value: u32,
+    pub row: usize,
+    pub columns: RangeInclusive<usize>,
+}
+
+impl PartNumber {
+    pub fn is_adjacent_to(&self, symbol: &Symbol) -> bool {
+        self.row.abs_diff(symbol.row) <= 1
+            && symbol.column + 1 >= *self.columns.start()
+            && symbol.column <= self.columns.end() + 1
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub grapheme: String,
+    pub row: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseError {
+    // a run of digits whose value does not fit into `u32`, starting at the given grapheme
+    NumberTooLarge { row: usize, column: usize },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::NumberTooLarge { row, column } => write!(
+                f,
+                "Part number at row {row}, column {column} does not fit into u32"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, Default)]
+pub struct Schematic {
+    // both vectors are sorted by row, which allows to only look at the neighbouring rows
+    numbers: Vec<PartNumber>,
+    symbols: Vec<Symbol>,
+}
+
+impl Schematic {
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        Self::parse_with(input, &SymbolSet::any())
+    }
+
+    pub fn parse_with(input: &str, symbol_set: &SymbolSet) -> Result<Self, ParseError> {
+        let mut schematic = Self::default();
+        let lines = input.lines().filter_map(|l| match l.trim() {
+            trimmed if !trimmed.is_empty() => Some(trimmed),
+            _ => None,
+        });
+
+        for (row, line) in lines.enumerate() {
+            // (value, first column) of the number that is currently being read
+            let mut current: Option<(u32, usize)> = None;
+            for (column, grapheme) in line.graphemes(true).enumerate() {
+                if let Some(digit) = digit_value(grapheme) {
+                    let (value, start) = current.unwrap_or((0, column));
+                    let value = value
+                        .checked_mul(10)
+                        .and_then(|v| v.checked_add(digit))
+                        .ok_or(ParseError::NumberTooLarge { row, column: start })?;
+                    current = Some((value, start));
+                    continue;
+                }
+
+                if let Some((value, start)) = current.take() {
+                    schematic.push_number(value, row, start..=column - 1);
+                }
+
+                if symbol_set.contains(grapheme) {
+                    schematic.symbols.push(Symbol {
+                        grapheme: grapheme.to_string(),
+                        row,
+                        column,
+                    });
+                }
+            }
+
+            if let Some((value, start)) = current {
+                let end = line.graphemes(true).count() - 1;
+                schematic.push_number(value, row, start..=end);
+            }
+        }
+        Ok(schematic)
+    }
+
+    fn push_number(&mut self, value: u32, row: usize, columns: RangeInclusive<usize>) {
+        self.numbers.push(PartNumber {
+            value,
+            row,
+            columns,
+        });
+    }
+
+    pub fn numbers(&self) -> &[PartNumber] {
+        &self.numbers
+    }
+
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    // all numbers that are adjacent to at least one symbol
+    pub fn part_numbers(&self) -> impl Iterator<Item = &PartNumber> {
+        self.numbers.iter().filter(|number| {
+            rows_around(&self.symbols, number.row, |s| s.row)
+                .iter()
+                .any(|symbol| number.is_adjacent_to(symbol))
+        })
+    }
+
+    pub fn adjacent_numbers<'a>(
+        &'a self,
+        symbol: &'a Symbol,
+    ) -> impl Iterator<Item = &'a PartNumber> {
+        rows_around(&self.numbers, symbol.row, |n| n.row)
+            .iter()
+            .filter(|number| number.is_adjacent_to(symbol))
+    }
+
+    // a gear is a `gear` symbol that has exactly two adjacent numbers; the product of two `u32`
+    // values always fits into `u64`
+    pub fn gear_ratios<'a>(&'a self, gear: &'a str) -> impl Iterator<Item = u64> + 'a {
+        self.symbols
+            .iter()
+            .filter(move |symbol| symbol.grapheme == gear)
+            .filter_map(|symbol| {
+                let numbers: Vec<&PartNumber> = self.adjacent_numbers(symbol).collect();
+                match numbers[..] {
+                    [first, second] => Some(first.value as u64 * second.value as u64),
+                    _ => None,
+                }
+            })
+    }
+}
+
+// returns the slice of row-sorted items that lie on the given row or on one of its neighbours
+fn rows_around<T>(items: &[T], row: usize, row_of: impl Fn(&T) -> usize) -> &[T] {
+    let start = items.partition_point(|item| row_of(item) + 1 < row);
+    let end = items.partition_point(|item| row_of(item) <= row + 1);
+    &items[start..end]
+}
+
+// both ASCII and fullwidth (U+FF10..U+FF19) digits are supported
+fn digit_value(grapheme: &str) -> Option<u32> {
+    let mut chars = grapheme.chars();
+    let c = chars.next()?;
+    if chars.next().is_some() {
+        return None;
+    }
+
+    match c {
+        '0'..='9' => c.to_digit(10),
+        '０'..='９' => Some(c as u32 - '０' as u32),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = r#"
+        467..114..
+        ...*......
+        ..35..633.
+        ......#...
+        617*......
+        .....+.58.
+        ..592.....
+        ......755.
+        ...$.*....
+        .664.598..
+    "#;
+
+    #[test]
+    fn ascii_schematic() {
+        let schematic = Schematic::parse(EXAMPLE).unwrap();
+        assert_eq!(4361, schematic.part_numbers().map(|n| n.value).sum::<u32>());
+        assert_eq!(467835, schematic.gear_ratios("*").sum::<u64>());
+    }
+
+    #[test]
+    fn unicode_schematic() {
+        // multi-byte symbols, a symbol made of several code points and fullwidth digits
+        let input = r#"
+            ４６７..114..
+            ...★......
+            ..35..633.
+            ......⚙️...
+            617★......
+            .....é.58.
+            ..592.....
+            ......755.
+            ...€.★....
+            .664.598..
+        "#;
+        let schematic = Schematic::parse(input).unwrap();
+        assert_eq!(
+            Some(&PartNumber {
+                value: 467,
+                row: 0,
+                columns: 0..=2
+            }),
+            schematic.numbers().first()
+        );
+        assert_eq!(
+            Some("⚙️"),
+            schematic.symbols().get(1).map(|s| s.grapheme.as_str())
+        );
+        assert_eq!(4361, schematic.part_numbers().map(|n| n.value).sum::<u32>());
+        assert_eq!(467835, schematic.gear_ratios("★").sum::<u64>());
+    }
+
+    #[test]
+    fn custom_symbol_set() {
+        let input = r#"
+            12.⚙️34..
+            ........
+            56.#.78★
+        "#;
+        let schematic = Schematic::parse_with(input, &SymbolSet::only(["⚙️", "★"])).unwrap();
+        let part_numbers: Vec<u32> = schematic.part_numbers().map(|n| n.value).collect();
+        assert_eq!(vec![34, 78], part_numbers);
+    }
+
+    #[test]
+    fn large_numbers() {
+        let input = "4294967295*4294967295\n..12345678901.";
+        assert_eq!(
+            Err(ParseError::NumberTooLarge { row: 1, column: 2 }),
+            Schematic::parse(input).map(|_| ())
+        );
+
+        let schematic = Schematic::parse("4294967295*4294967295").unwrap();
+        assert_eq!(
+            vec![u32::MAX as u64 * u32::MAX as u64],
+            schematic.gear_ratios("*").collect::<Vec<u64>>()
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use day_04::{parse_cards, DoublingPoints, Simulator};
 
 fn main() {
     let input = include_str!("./input.txt");
@@ -6,43 +6,9 @@ fn main() {
     println!("Part 1 answer: {answer}");
 }
 
-fn process(input: &str) -> u32 {
-    let lines: Vec<&str> = input
-        .lines()
-        .filter_map(|l| match l.trim() {
-            trimmed if !trimmed.is_empty() => Some(trimmed),
-            _ => None,
-        })
-        .collect();
-
-    let mut total: u32 = 0;
-    for line in lines {
-        if let Some((_, lists)) = line.split_once(':') {
-            if let Some((winning_input, our_input)) = lists.split_once('|') {
-                let winning = parse_list(winning_input);
-                let our = parse_list(our_input);
-                let num_common = our.intersection(&winning).count();
-
-                // every new match doubles the score
-                if num_common > 0 {
-                    total += 2u32
-                        .checked_pow(num_common as u32 - 1)
-                        .expect("failed to raise to pow");
-                }
-            }
-        }
-    }
-    total
-}
-
-fn parse_list(input: &str) -> HashSet<u32> {
-    input
-        .split(' ')
-        .filter_map(|s| match s.trim() {
-            num if !num.is_empty() => Some(num.parse::<u32>().expect("failed to parse number")),
-            _ => None,
-        })
-        .collect()
+fn process(input: &str) -> u64 {
+    let cards = parse_cards(input).expect("failed to parse cards");
+    Simulator::new(&cards, DoublingPoints).run().points()
 }
 
 #[cfg(test)]
@@ -1,4 +1,4 @@
-use std::collections::{HashMap, HashSet};
+use day_04::{parse_cards, CopyFollowing, Overflow, Simulator};
 
 fn main() {
     let input = include_str!("./input.txt");
@@ -7,51 +7,11 @@ fn main() {
 }
 
 fn process(input: &str) -> usize {
-    let lines: Vec<&str> = input
-        .lines()
-        .filter_map(|l| match l.trim() {
-            trimmed if !trimmed.is_empty() => Some(trimmed),
-            _ => None,
-        })
-        .collect();
-
-    // Since cards have continuously increasing ids, we can use a vector to save per-card scores
-    let mut cards: Vec<usize> = Vec::new();
-    for line in lines {
-        if let Some((_, lists)) = line.split_once(':') {
-            if let Some((winning_input, our_input)) = lists.split_once('|') {
-                let winning = parse_list(winning_input);
-                let our = parse_list(our_input);
-                cards.push(our.intersection(&winning).count());
-            }
-        }
-    }
-    count_cards(cards)
-}
-
-fn parse_list(input: &str) -> HashSet<u32> {
-    input
-        .split(' ')
-        .filter_map(|s| match s.trim() {
-            num if !num.is_empty() => Some(num.parse::<u32>().expect("failed to parse number")),
-            _ => None,
-        })
-        .collect()
-}
-
-fn count_cards(cards: Vec<usize>) -> usize {
-    let mut counts: HashMap<usize, usize> = HashMap::new();
-    for (id, num_common) in cards.iter().enumerate() {
-        let count = *counts.entry(id).and_modify(|curr| *curr += 1).or_insert(1);
-        for i in 1..=*num_common {
-            let copy_id = id + i;
-            counts
-                .entry(copy_id)
-                .and_modify(|curr| *curr += count)
-                .or_insert(count);
-        }
-    }
-    counts.values().sum()
+    let cards = parse_cards(input).expect("failed to parse cards");
+    let rule = CopyFollowing {
+        overflow: Overflow::Discard,
+    };
+    Simulator::new(&cards, rule).run().total_cards()
 }
 
 #[cfg(test)]
@@ -0,0 +1,368 @@
+use std::{
+    collections::{BTreeSet, HashSet},
+    fmt,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Card {
+    pub id: usize,
+    pub winning: HashSet<u32>,
+    pub ours: HashSet<u32>,
+}
+
+impl Card {
+    pub fn matches(&self) -> usize {
+        self.ours.intersection(&self.winning).count()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CardError {
+    // the line is not `Card <id>: <numbers> | <numbers>`
+    Malformed { line: String },
+    // cards are numbered 1, 2, 3, ... in the order they are listed
+    UnexpectedId { expected: usize, found: usize },
+}
+
+impl fmt::Display for CardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CardError::Malformed { line } => write!(f, "Malformed card: {line}"),
+            CardError::UnexpectedId { expected, found } => {
+                write!(f, "Expected card {expected}, found card {found}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CardError {}
+
+// Cards must be listed in order of their ids, starting from 1, so that the card at position `p`
+// in the table is always card `p + 1`.
+pub fn parse_cards(input: &str) -> Result<Vec<Card>, CardError> {
+    input
+        .lines()
+        .filter_map(|l| match l.trim() {
+            trimmed if !trimmed.is_empty() => Some(trimmed),
+            _ => None,
+        })
+        .enumerate()
+        .map(|(position, line)| {
+            let card = parse_card(line).ok_or_else(|| CardError::Malformed {
+                line: line.to_string(),
+            })?;
+            match card.id {
+                id if id == position + 1 => Ok(card),
+                found => Err(CardError::UnexpectedId {
+                    expected: position + 1,
+                    found,
+                }),
+            }
+        })
+        .collect()
+}
+
+fn parse_card(line: &str) -> Option<Card> {
+    let (name, lists) = line.split_once(':')?;
+    let (winning_input, our_input) = lists.split_once('|')?;
+    let id = name.strip_prefix("Card")?.trim().parse().ok()?;
+    Some(Card {
+        id,
+        winning: parse_list(winning_input)?,
+        ours: parse_list(our_input)?,
+    })
+}
+
+fn parse_list(input: &str) -> Option<HashSet<u32>> {
+    input
+        .split_whitespace()
+        .map(|num| num.parse().ok())
+        .collect()
+}
+
+// what happens with prizes that point past the first or the last card
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    // such prizes are discarded
+    Discard,
+    // such prizes continue from the other end of the table
+    Wrap,
+}
+
+impl Overflow {
+    fn resolve(self, target: isize, num_cards: usize) -> Option<usize> {
+        match self {
+            Overflow::Discard => usize::try_from(target).ok().filter(|&t| t < num_cards),
+            Overflow::Wrap if num_cards == 0 => None,
+            Overflow::Wrap => Some(target.rem_euclid(num_cards as isize) as usize),
+        }
+    }
+}
+
+// cards are addressed by their position in the table, which is their id minus one
+pub trait PrizeRule {
+    // positions of the cards won by a single copy of the card at `position`
+    fn copies_won(&self, _position: usize, _matches: usize, _num_cards: usize) -> Vec<usize> {
+        Vec::new()
+    }
+
+    // points scored by a single copy of a card
+    fn points(&self, _matches: usize) -> u64 {
+        0
+    }
+}
+
+// the first match is worth one point, every other match doubles the score, saturating at
+// `u64::MAX` beyond 64 matches
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DoublingPoints;
+
+impl PrizeRule for DoublingPoints {
+    fn points(&self, matches: usize) -> u64 {
+        match matches {
+            0 => 0,
+            m => u32::try_from(m - 1)
+                .ok()
+                .and_then(|exponent| 2u64.checked_pow(exponent))
+                .unwrap_or(u64::MAX),
+        }
+    }
+}
+
+// `n` matches win a copy of each of the `n` cards below the winning card
+#[derive(Debug, Clone, Copy)]
+pub struct CopyFollowing {
+    pub overflow: Overflow,
+}
+
+impl PrizeRule for CopyFollowing {
+    fn copies_won(&self, position: usize, matches: usize, num_cards: usize) -> Vec<usize> {
+        (1..=matches)
+            .filter_map(|i| {
+                self.overflow
+                    .resolve(position as isize + i as isize, num_cards)
+            })
+            .collect()
+    }
+}
+
+// `n` matches win a copy of each of the `n` cards above the winning card
+#[derive(Debug, Clone, Copy)]
+pub struct CopyPreceding {
+    pub overflow: Overflow,
+}
+
+impl PrizeRule for CopyPreceding {
+    fn copies_won(&self, position: usize, matches: usize, num_cards: usize) -> Vec<usize> {
+        (1..=matches)
+            .filter_map(|i| {
+                self.overflow
+                    .resolve(position as isize - i as isize, num_cards)
+            })
+            .collect()
+    }
+}
+
+// copies won by one card for another card
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Transfer {
+    pub from: usize,
+    pub to: usize,
+    pub copies: usize,
+}
+
+// Plays the table card by card. Every card is scored exactly once, with all the copies it has at
+// that moment. Copies won for cards that were already scored are still counted, but they do not
+// win anything themselves, which guarantees that the simulation ends for any prize rule. Points
+// and copies saturate instead of overflowing.
+pub struct Simulator<'a, R: PrizeRule> {
+    cards: &'a [Card],
+    rule: R,
+    copies: Vec<usize>,
+    points: u64,
+    next: usize,
+    transfers: Vec<Transfer>,
+}
+
+impl<'a, R: PrizeRule> Simulator<'a, R> {
+    pub fn new(cards: &'a [Card], rule: R) -> Self {
+        Self {
+            cards,
+            rule,
+            copies: vec![1; cards.len()],
+            points: 0,
+            next: 0,
+            transfers: Vec::new(),
+        }
+    }
+
+    // scores the next card and returns the number of copies of each card afterwards
+    pub fn step(&mut self) -> Option<&[usize]> {
+        let position = self.next;
+        let card = self.cards.get(position)?;
+        self.next += 1;
+
+        let count = self.copies[position];
+        let matches = card.matches();
+        let points = self.rule.points(matches).saturating_mul(count as u64);
+        self.points = self.points.saturating_add(points);
+        for to in self.rule.copies_won(position, matches, self.cards.len()) {
+            self.copies[to] = self.copies[to].saturating_add(count);
+            self.transfers.push(Transfer {
+                from: position,
+                to,
+                copies: count,
+            });
+        }
+        Some(&self.copies)
+    }
+
+    // plays all remaining cards, returning the copies of each card after every step
+    pub fn trace(&mut self) -> Vec<Vec<usize>> {
+        let mut trace = Vec::new();
+        while let Some(copies) = self.step() {
+            trace.push(copies.to_vec());
+        }
+        trace
+    }
+
+    pub fn run(mut self) -> Self {
+        while self.step().is_some() {}
+        self
+    }
+
+    pub fn copies(&self) -> &[usize] {
+        &self.copies
+    }
+
+    pub fn total_cards(&self) -> usize {
+        self.copies
+            .iter()
+            .fold(0, |total: usize, &c| total.saturating_add(c))
+    }
+
+    pub fn points(&self) -> u64 {
+        self.points
+    }
+
+    // all transfers that contributed, directly or through other copies, to the copies of the
+    // card at `position`
+    pub fn genealogy(&self, position: usize) -> Vec<Transfer> {
+        let mut ancestors: BTreeSet<usize> = BTreeSet::from([position]);
+        let mut genealogy: Vec<Transfer> = Vec::new();
+        let mut frontier = vec![position];
+        while let Some(card) = frontier.pop() {
+            for transfer in self.transfers.iter().filter(|t| t.to == card) {
+                genealogy.push(*transfer);
+                if ancestors.insert(transfer.from) {
+                    frontier.push(transfer.from);
+                }
+            }
+        }
+        genealogy.sort();
+        genealogy
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = r#"
+        Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+        Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+        Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+        Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+        Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+        Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11
+    "#;
+
+    #[test]
+    fn copy_trace() {
+        let cards = parse_cards(EXAMPLE).unwrap();
+        let mut simulator = Simulator::new(
+            &cards,
+            CopyFollowing {
+                overflow: Overflow::Discard,
+            },
+        );
+        let trace = simulator.trace();
+        assert_eq!(vec![1, 2, 2, 2, 2, 1], trace[0]);
+        assert_eq!(vec![1, 2, 4, 8, 14, 1], trace[5]);
+        assert_eq!(30, simulator.total_cards());
+        assert_eq!(
+            vec![
+                Transfer {
+                    from: 0,
+                    to: 1,
+                    copies: 1
+                },
+                Transfer {
+                    from: 0,
+                    to: 2,
+                    copies: 1
+                },
+                Transfer {
+                    from: 1,
+                    to: 2,
+                    copies: 2
+                },
+            ],
+            simulator.genealogy(2)
+        );
+    }
+
+    #[test]
+    fn overflowing_prizes() {
+        let cards = parse_cards(EXAMPLE).unwrap();
+        let discarded = Simulator::new(
+            &cards,
+            CopyPreceding {
+                overflow: Overflow::Discard,
+            },
+        )
+        .run();
+        assert_eq!(vec![3, 2, 2, 1, 1, 1], discarded.copies());
+
+        let wrapped = Simulator::new(
+            &cards,
+            CopyPreceding {
+                overflow: Overflow::Wrap,
+            },
+        )
+        .run();
+        assert_eq!(vec![4, 3, 4, 2, 2, 3], wrapped.copies());
+    }
+
+    #[test]
+    fn doubling_points() {
+        let cards = parse_cards(EXAMPLE).unwrap();
+        assert_eq!(13, Simulator::new(&cards, DoublingPoints).run().points());
+    }
+
+    #[test]
+    fn invalid_tables_and_large_prizes() {
+        assert_eq!(
+            Err(CardError::UnexpectedId {
+                expected: 2,
+                found: 3
+            }),
+            parse_cards("Card 1: 1 | 1\nCard 3: 2 | 2")
+        );
+        assert!(matches!(
+            parse_cards("Card 1: 1 | x"),
+            Err(CardError::Malformed { .. })
+        ));
+        assert!(parse_cards("Card 1 1 | 1").is_err());
+
+        assert_eq!(1 << 63, DoublingPoints.points(64));
+        assert_eq!(u64::MAX, DoublingPoints.points(65));
+        let numbers: Vec<String> = (1..=70).map(|n| n.to_string()).collect();
+        let line = format!("Card 1: {0} | {0}", numbers.join(" "));
+        let cards = parse_cards(&format!("{line}\n{}", line.replace("Card 1", "Card 2"))).unwrap();
+        assert_eq!(
+            u64::MAX,
+            Simulator::new(&cards, DoublingPoints).run().points()
+        );
+    }
+}
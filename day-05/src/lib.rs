@@ -0,0 +1,564 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    ops::Range,
+    slice,
+    str::FromStr,
+};
+
+use anyhow::{anyhow, bail, Context, Error, Result};
+
+// (source, destination) ranges of a single almanac row
+type Row = (Range<usize>, Range<usize>);
+
+// A contiguous range of source values that are all shifted by the same offset. Sources and
+// destinations span all of `usize`, so the offset between them needs a wider type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Piece {
+    pub source: Range<usize>,
+    pub offset: i128,
+}
+
+impl Piece {
+    fn apply(&self, value: usize) -> usize {
+        shift(value, self.offset)
+    }
+}
+
+// Every piece is built from destinations that fit into `usize`, so shifting the values it covers
+// (or the ends of ranges of them) never leaves `usize`; the clamp only keeps that from panicking.
+fn shift(value: usize, offset: i128) -> usize {
+    (value as i128 + offset).clamp(0, usize::MAX as i128) as usize
+}
+
+// A map from source to destination values that is defined piece by piece. Pieces are sorted and
+// do not overlap, values that are not covered by any piece are mapped onto themselves.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PiecewiseMap {
+    pieces: Vec<Piece>,
+}
+
+impl PiecewiseMap {
+    pub fn identity() -> Self {
+        Self::default()
+    }
+
+    // builds a map from (source, destination) rows of an almanac block; rows with overlapping
+    // sources or overlapping destinations are rejected, since they make the map ambiguous
+    pub fn from_rows(rows: &[Row]) -> Result<Self> {
+        let mut rows: Vec<&Row> = rows
+            .iter()
+            .filter(|(source, _)| !source.is_empty())
+            .collect();
+
+        rows.sort_by_key(|(_, destination)| destination.start);
+        for pair in rows.windows(2) {
+            if pair[1].1.start < pair[0].1.end {
+                bail!(
+                    "Conflicting rows: {:?} and {:?} map onto overlapping destinations",
+                    pair[0].0,
+                    pair[1].0
+                );
+            }
+        }
+
+        rows.sort_by_key(|(source, _)| source.start);
+        for pair in rows.windows(2) {
+            if pair[1].0.start < pair[0].0.end {
+                bail!(
+                    "Overlapping rows: {:?} and {:?} share source values",
+                    pair[0].0,
+                    pair[1].0
+                );
+            }
+        }
+
+        let pieces = rows
+            .into_iter()
+            .map(|(source, destination)| Piece {
+                source: source.clone(),
+                offset: destination.start as i128 - source.start as i128,
+            })
+            .collect();
+        Ok(Self::from_sorted_pieces(pieces))
+    }
+
+    // merges adjacent pieces that share the same offset and drops identity pieces
+    fn from_sorted_pieces(pieces: Vec<Piece>) -> Self {
+        let mut merged: Vec<Piece> = Vec::with_capacity(pieces.len());
+        for piece in pieces.into_iter().filter(|p| p.offset != 0) {
+            match merged.last_mut() {
+                Some(last)
+                    if last.source.end == piece.source.start && last.offset == piece.offset =>
+                {
+                    last.source.end = piece.source.end;
+                }
+                _ => merged.push(piece),
+            }
+        }
+        Self { pieces: merged }
+    }
+
+    pub fn pieces(&self) -> &[Piece] {
+        &self.pieces
+    }
+
+    // looks up a value in O(log n)
+    pub fn get(&self, value: usize) -> usize {
+        let idx = self.pieces.partition_point(|p| p.source.end <= value);
+        match self.pieces.get(idx) {
+            Some(piece) if piece.source.contains(&value) => piece.apply(value),
+            _ => value,
+        }
+    }
+
+    // splits the range into consecutive segments, each of which is shifted by a single offset
+    fn segments(&self, range: Range<usize>) -> Vec<(Range<usize>, i128)> {
+        let mut segments = Vec::new();
+        let mut start = range.start;
+        let first = self.pieces.partition_point(|p| p.source.end <= start);
+        for piece in self.pieces[first..].iter() {
+            if start >= range.end {
+                break;
+            }
+            if piece.source.start >= range.end {
+                break;
+            }
+
+            // gap before the piece maps onto itself
+            if start < piece.source.start {
+                segments.push((start..piece.source.start, 0));
+                start = piece.source.start;
+            }
+
+            let end = piece.source.end.min(range.end);
+            segments.push((start..end, piece.offset));
+            start = end;
+        }
+
+        if start < range.end {
+            segments.push((start..range.end, 0));
+        }
+        segments
+    }
+
+    // maps all source ranges onto sorted, non-overlapping destination ranges
+    pub fn image(&self, ranges: &[Range<usize>]) -> Vec<Range<usize>> {
+        let mut image: Vec<Range<usize>> = ranges
+            .iter()
+            .flat_map(|r| self.segments(r.clone()))
+            .map(|(r, offset)| shift(r.start, offset)..shift(r.end, offset))
+            .collect();
+        merge_ranges(&mut image);
+        image
+    }
+
+    pub fn min_image(&self, ranges: &[Range<usize>]) -> Option<usize> {
+        self.image(ranges).first().map(|r| r.start)
+    }
+
+    // all source values that are mapped into the given destination ranges; since unmapped values
+    // map onto themselves, a single destination value can have several sources
+    pub fn preimage(&self, ranges: &[Range<usize>]) -> Vec<Range<usize>> {
+        let mut preimage: Vec<Range<usize>> = Vec::new();
+        for (source, offset) in self.segments(0..usize::MAX) {
+            let image_start = shift(source.start, offset);
+            let image_end = shift(source.end, offset);
+            for r in ranges.iter() {
+                let start = r.start.max(image_start);
+                let end = r.end.min(image_end);
+                if start < end {
+                    preimage.push(shift(start, -offset)..shift(end, -offset));
+                }
+            }
+        }
+        merge_ranges(&mut preimage);
+        preimage
+    }
+
+    // looks up all source values that map onto the given destination value
+    pub fn get_inverse(&self, value: usize) -> Vec<usize> {
+        // pieces end before `usize::MAX`, so only the value itself maps onto it
+        let Some(end) = value.checked_add(1) else {
+            return vec![value];
+        };
+        let range = value..end;
+        self.preimage(slice::from_ref(&range))
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    // returns the map that applies `self` first and `next` afterwards
+    pub fn then(&self, next: &PiecewiseMap) -> Result<PiecewiseMap> {
+        let mut pieces: Vec<Piece> = Vec::new();
+        for (source, offset) in self.segments(0..usize::MAX) {
+            let image_start = shift(source.start, offset);
+            let image_end = shift(source.end, offset);
+            for (image, next_offset) in next.segments(image_start..image_end) {
+                let source = shift(image.start, -offset)..shift(image.end, -offset);
+                let offset = offset + next_offset;
+                let destination_end = source.end as i128 + offset;
+                if destination_end < 0 || destination_end > usize::MAX as i128 {
+                    bail!("Composed map sends {source:?} outside of usize");
+                }
+                pieces.push(Piece { source, offset });
+            }
+        }
+        Ok(Self::from_sorted_pieces(pieces))
+    }
+}
+
+// sorts the ranges and merges all of them that overlap or touch
+fn merge_ranges(ranges: &mut Vec<Range<usize>>) {
+    ranges.retain(|r| !r.is_empty());
+    ranges.sort_by_key(|r| r.start);
+    let mut merged: Vec<Range<usize>> = Vec::with_capacity(ranges.len());
+    for r in ranges.drain(..) {
+        match merged.last_mut() {
+            Some(last) if r.start <= last.end => last.end = last.end.max(r.end),
+            _ => merged.push(r),
+        }
+    }
+    *ranges = merged;
+}
+
+#[derive(Debug, Clone)]
+pub struct NamedMap {
+    pub source: String,
+    pub destination: String,
+    pub map: PiecewiseMap,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Almanac {
+    pub seeds: Vec<usize>,
+    maps: Vec<NamedMap>,
+}
+
+impl Almanac {
+    pub fn maps(&self) -> &[NamedMap] {
+        &self.maps
+    }
+
+    // seed numbers interpreted as (start, length) pairs
+    pub fn seed_ranges(&self) -> Result<Vec<Range<usize>>> {
+        self.seeds
+            .chunks_exact(2)
+            .map(|c| match c[0].checked_add(c[1]) {
+                Some(end) => Ok(c[0]..end),
+                None => bail!("Seed range does not fit into usize: {} {}", c[0], c[1]),
+            })
+            .collect()
+    }
+
+    // chain of maps leading from one category to another, found using breadth-first search
+    fn path(&self, from: &str, to: &str) -> Result<Vec<&NamedMap>> {
+        let mut previous: HashMap<&str, &NamedMap> = HashMap::new();
+        let mut queue: VecDeque<&str> = VecDeque::from([from]);
+        while let Some(category) = queue.pop_front() {
+            if category == to {
+                break;
+            }
+            for named in self.maps.iter().filter(|m| m.source == category) {
+                let destination = named.destination.as_str();
+                if destination != from && !previous.contains_key(destination) {
+                    previous.insert(destination, named);
+                    queue.push_back(destination);
+                }
+            }
+        }
+
+        let mut path = Vec::new();
+        let mut category = to;
+        while category != from {
+            let named = previous
+                .get(category)
+                .ok_or_else(|| anyhow!("No chain of maps from {from} to {to}"))?;
+            path.push(*named);
+            category = named.source.as_str();
+        }
+        path.reverse();
+        Ok(path)
+    }
+
+    fn push_block(&mut self, source: String, destination: String, rows: &[Row]) -> Result<()> {
+        if self
+            .maps
+            .iter()
+            .any(|m| m.source == source && m.destination == destination)
+        {
+            bail!("Duplicate map: {source}-to-{destination}");
+        }
+        let map = PiecewiseMap::from_rows(rows)
+            .with_context(|| format!("Invalid {source}-to-{destination} map"))?;
+        self.maps.push(NamedMap {
+            source,
+            destination,
+            map,
+        });
+        Ok(())
+    }
+
+    // collapses the chain of maps between two categories into a single map
+    pub fn compose(&self, from: &str, to: &str) -> Result<PiecewiseMap> {
+        self.path(from, to)?
+            .into_iter()
+            .try_fold(PiecewiseMap::identity(), |acc, named| acc.then(&named.map))
+    }
+
+    // values of every category on the way from one category to another that can be reached from
+    // the given source ranges
+    pub fn coverage(&self, from: &str, to: &str, ranges: &[Range<usize>]) -> Result<Vec<Coverage>> {
+        let mut reachable = ranges.to_vec();
+        merge_ranges(&mut reachable);
+        let mut report = vec![Coverage {
+            category: from.to_string(),
+            reachable: reachable.clone(),
+        }];
+        for named in self.path(from, to)? {
+            reachable = named.map.image(&reachable);
+            report.push(Coverage {
+                category: named.destination.clone(),
+                reachable: reachable.clone(),
+            });
+        }
+        Ok(report)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Coverage {
+    pub category: String,
+    // sorted, non-overlapping ranges
+    pub reachable: Vec<Range<usize>>,
+}
+
+impl Coverage {
+    pub fn len(&self) -> usize {
+        self.reachable.iter().map(|r| r.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.reachable.is_empty()
+    }
+
+    pub fn contains(&self, value: usize) -> bool {
+        let idx = self.reachable.partition_point(|r| r.end <= value);
+        self.reachable.get(idx).is_some_and(|r| r.contains(&value))
+    }
+
+    // unreachable ranges between the first and the last reachable values
+    pub fn gaps(&self) -> Vec<Range<usize>> {
+        self.reachable
+            .windows(2)
+            .map(|pair| pair[0].end..pair[1].start)
+            .collect()
+    }
+}
+
+impl FromStr for Almanac {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut almanac = Almanac::default();
+        let mut lines = s.lines().map(|l| l.trim()).filter(|l| !l.is_empty());
+
+        let seeds = lines
+            .next()
+            .and_then(|l| l.strip_prefix("seeds:"))
+            .ok_or_else(|| anyhow!("No seeds found"))?;
+        almanac.seeds = parse_numbers(seeds)?;
+
+        let mut current: Option<(String, String, Vec<Row>)> = None;
+        for line in lines {
+            if let Some(header) = line.strip_suffix("map:") {
+                if let Some((source, destination, rows)) = current.take() {
+                    almanac.push_block(source, destination, &rows)?;
+                }
+                let (source, destination) = header
+                    .trim()
+                    .split_once("-to-")
+                    .ok_or_else(|| anyhow!("Failed to parse a map header: {line}"))?;
+                current = Some((source.to_string(), destination.to_string(), Vec::new()));
+                continue;
+            }
+
+            let Some((_, _, rows)) = current.as_mut() else {
+                bail!("Map row without a header: {line}");
+            };
+            rows.push(parse_row(line)?);
+        }
+
+        if let Some((source, destination, rows)) = current {
+            almanac.push_block(source, destination, &rows)?;
+        }
+        Ok(almanac)
+    }
+}
+
+fn parse_numbers(s: &str) -> Result<Vec<usize>> {
+    s.split_whitespace()
+        .map(|num| {
+            num.parse()
+                .map_err(|_| anyhow!("Failed to parse a number: {num}"))
+        })
+        .collect()
+}
+
+fn parse_row(row: &str) -> Result<Row> {
+    let nums = parse_numbers(row)?;
+    if nums.len() != 3 {
+        bail!("Expected three numbers in row: {row}");
+    }
+
+    let dst_start = nums[0];
+    let src_start = nums[1];
+    let length = nums[2];
+    let (Some(src_end), Some(dst_end)) =
+        (src_start.checked_add(length), dst_start.checked_add(length))
+    else {
+        bail!("Row does not fit into usize: {row}");
+    };
+    Ok((src_start..src_end, dst_start..dst_end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = r#"
+        seeds: 79 14 55 13
+
+        seed-to-soil map:
+        50 98 2
+        52 50 48
+
+        soil-to-fertilizer map:
+        0 15 37
+        37 52 2
+        39 0 15
+
+        fertilizer-to-water map:
+        49 53 8
+        0 11 42
+        42 0 7
+        57 7 4
+
+        water-to-light map:
+        88 18 7
+        18 25 70
+
+        light-to-temperature map:
+        45 77 23
+        81 45 19
+        68 64 13
+
+        temperature-to-humidity map:
+        0 69 1
+        1 0 69
+
+        humidity-to-location map:
+        60 56 37
+        56 93 4
+    "#;
+
+    #[test]
+    fn composed_chain() {
+        let almanac: Almanac = EXAMPLE.parse().unwrap();
+        let seed_to_location = almanac.compose("seed", "location").unwrap();
+        let locations: Vec<usize> = almanac
+            .seeds
+            .iter()
+            .map(|&seed| seed_to_location.get(seed))
+            .collect();
+        assert_eq!(vec![82, 43, 86, 35], locations);
+        assert_eq!(
+            Some(46),
+            seed_to_location.min_image(&almanac.seed_ranges().unwrap())
+        );
+    }
+
+    #[test]
+    fn partial_chain() {
+        let almanac: Almanac = EXAMPLE.parse().unwrap();
+        let soil_to_water = almanac.compose("soil", "water").unwrap();
+        assert_eq!(27, soil_to_water.get(53));
+        assert!(almanac.compose("location", "seed").is_err());
+        assert_eq!(
+            PiecewiseMap::identity(),
+            almanac.compose("light", "light").unwrap()
+        );
+    }
+
+    #[test]
+    fn composition_matches_step_by_step_lookup() {
+        let almanac: Almanac = EXAMPLE.parse().unwrap();
+        let seed_to_location = almanac.compose("seed", "location").unwrap();
+        for seed in 0..200 {
+            let location = almanac
+                .maps()
+                .iter()
+                .fold(seed, |value, named| named.map.get(value));
+            assert_eq!(location, seed_to_location.get(seed));
+        }
+    }
+
+    #[test]
+    fn inverse_lookups() {
+        let almanac: Almanac = EXAMPLE.parse().unwrap();
+        let seed_to_soil = &almanac.maps()[0].map;
+        assert_eq!(vec![98], seed_to_soil.get_inverse(50));
+        assert_eq!(vec![97], seed_to_soil.get_inverse(99));
+        assert_eq!(vec![10], seed_to_soil.get_inverse(10));
+
+        let seed_to_location = almanac.compose("seed", "location").unwrap();
+        for seed in 0..200 {
+            let location = seed_to_location.get(seed);
+            assert!(seed_to_location.get_inverse(location).contains(&seed));
+        }
+        assert_eq!(vec![usize::MAX], seed_to_location.get_inverse(usize::MAX));
+        let seeds = seed_to_location.preimage(slice::from_ref(&(46..47)));
+        assert!(seeds.iter().any(|r| r.contains(&82)));
+    }
+
+    #[test]
+    fn coverage_report() {
+        let almanac: Almanac = EXAMPLE.parse().unwrap();
+        let report = almanac
+            .coverage("seed", "location", &almanac.seed_ranges().unwrap())
+            .unwrap();
+        assert_eq!(8, report.len());
+        assert_eq!("location", report[7].category);
+        assert_eq!(27, report[7].len());
+        assert!(report[7].contains(46));
+        assert!(!report[7].contains(45));
+    }
+
+    #[test]
+    fn conflicting_rows() {
+        let overlapping = "seeds: 1 2\n seed-to-soil map:\n 50 98 2\n 52 97 2";
+        assert!(overlapping.parse::<Almanac>().is_err());
+
+        let conflicting = "seeds: 1 2\n seed-to-soil map:\n 50 98 2\n 51 10 2";
+        assert!(conflicting.parse::<Almanac>().is_err());
+    }
+
+    #[test]
+    fn values_beyond_isize() {
+        let high = usize::MAX - 10;
+        let input = format!(
+            "seeds: {high} 20\n seed-to-soil map:\n 0 {high} 10\n soil-to-water map:\n {high} 0 3"
+        );
+        let almanac: Almanac = input.parse().unwrap();
+        assert!(almanac.seed_ranges().is_err());
+
+        let seed_to_soil = &almanac.maps()[0].map;
+        assert_eq!(2, seed_to_soil.get(high + 2));
+        assert_eq!(vec![2, high + 2], seed_to_soil.get_inverse(2));
+        let seed_to_water = almanac.compose("seed", "water").unwrap();
+        assert_eq!(high + 2, seed_to_water.get(high + 2));
+        assert_eq!(high + 1, seed_to_water.get(1));
+        let seeds = high + 5..high + 8;
+        assert_eq!(vec![5..8], seed_to_water.image(slice::from_ref(&seeds)));
+    }
+}
@@ -0,0 +1,256 @@
+use std::{ops::RangeInclusive, str::FromStr};
+
+use anyhow::{anyhow, bail, Result};
+use num::{integer::Roots, CheckedAdd, CheckedMul, CheckedSub, Integer};
+
+pub mod regatta;
+
+// numbers the solver can work with: fixed width unsigned integers and `BigUint`
+pub trait RaceNum:
+    Integer + Roots + Clone + CheckedAdd + CheckedMul + CheckedSub + From<u8>
+{
+}
+
+impl<N> RaceNum for N where
+    N: Integer + Roots + Clone + CheckedAdd + CheckedMul + CheckedSub + From<u8>
+{
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Race<N> {
+    pub time: N,
+    pub distance: N,
+}
+
+// Holding the button for `per` milliseconds increases the speed of the boat by `speed` millimeters
+// per millisecond. The original boats have a charge rate of 1 mm/ms per 1 ms.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChargeRate<N> {
+    pub speed: N,
+    pub per: N,
+}
+
+impl<N: RaceNum> ChargeRate<N> {
+    pub fn unit() -> Self {
+        Self {
+            speed: N::one(),
+            per: N::one(),
+        }
+    }
+}
+
+impl<N: RaceNum> Race<N> {
+    pub fn new(time: N, distance: N) -> Self {
+        Self { time, distance }
+    }
+
+    // whether holding the button for `hold` milliseconds beats the record, i.e.
+    // speed * hold * (time - hold) > per * distance
+    pub fn wins(&self, hold: &N, rate: &ChargeRate<N>) -> Result<bool> {
+        if hold > &self.time {
+            return Ok(false);
+        }
+        let travelled = rate
+            .speed
+            .checked_mul(hold)
+            .and_then(|d| d.checked_mul(&(self.time.clone() - hold.clone())))
+            .ok_or_else(overflow)?;
+        let record = rate.per.checked_mul(&self.distance).ok_or_else(overflow)?;
+        Ok(travelled > record)
+    }
+
+    // The exact range of winning hold times. The travelled distance is a parabola in the hold
+    // time, so the lower bound is found from the integer square root of its discriminant and then
+    // corrected by at most a couple of exact checks. The upper bound follows from symmetry.
+    pub fn winning_hold_times(&self, rate: &ChargeRate<N>) -> Result<Option<RangeInclusive<N>>> {
+        let one = N::one();
+        let two = N::from(2);
+        let four = N::from(4);
+
+        // roots of speed * h^2 - speed * time * h + per * distance = 0
+        let a = rate.speed.checked_mul(&self.time).ok_or_else(overflow)?;
+        let a_squared = a.checked_mul(&a).ok_or_else(overflow)?;
+        let c = four
+            .checked_mul(&rate.speed)
+            .and_then(|c| c.checked_mul(&rate.per))
+            .and_then(|c| c.checked_mul(&self.distance))
+            .ok_or_else(overflow)?;
+        if a_squared <= c {
+            return Ok(None);
+        }
+        let sqrt = (a_squared - c).sqrt();
+        let two_speed = two.checked_mul(&rate.speed).ok_or_else(overflow)?;
+        let mut lower = (a - sqrt) / two_speed;
+
+        let half_time = self.time.clone() / two;
+        while lower <= half_time && !self.wins(&lower, rate)? {
+            lower = lower + one.clone();
+        }
+        while lower > N::zero() && self.wins(&(lower.clone() - one.clone()), rate)? {
+            lower = lower - one.clone();
+        }
+
+        if !self.wins(&lower, rate)? {
+            return Ok(None);
+        }
+        let upper = self.time.clone() - lower.clone();
+        Ok(Some(lower..=upper))
+    }
+
+    pub fn num_ways_to_win(&self, rate: &ChargeRate<N>) -> Result<N> {
+        Ok(match self.winning_hold_times(rate)? {
+            Some(hold_times) => {
+                let (lower, upper) = hold_times.into_inner();
+                upper - lower + N::one()
+            }
+            None => N::zero(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kerning {
+    // every column of the sheet is a separate race
+    Unkerned,
+    // the spaces between digits are ignored, so the whole sheet is a single race
+    Kerned,
+}
+
+// reads the `Time:` and `Distance:` rows of a race sheet with any number of columns
+pub fn parse_sheet<N>(input: &str, kerning: Kerning) -> Result<Vec<Race<N>>>
+where
+    N: RaceNum + FromStr,
+{
+    let mut times: Option<Vec<N>> = None;
+    let mut distances: Option<Vec<N>> = None;
+    for line in input.lines().map(|l| l.trim()).filter(|l| !l.is_empty()) {
+        let Some((label, values)) = line.split_once(':') else {
+            bail!("Failed to parse a sheet row: {line}");
+        };
+        let values: Vec<&str> = match kerning {
+            Kerning::Unkerned => values.split_whitespace().collect(),
+            Kerning::Kerned => vec![values],
+        };
+        let values = values
+            .into_iter()
+            .map(|v| {
+                let v = v.replace(char::is_whitespace, "");
+                v.parse::<N>()
+                    .map_err(|_| anyhow!("Failed to parse a number: {v}"))
+            })
+            .collect::<Result<Vec<N>>>()?;
+        match label.trim() {
+            "Time" => times = Some(values),
+            "Distance" => distances = Some(values),
+            other => bail!("Unknown sheet row: {other}"),
+        }
+    }
+
+    let times = times.ok_or_else(|| anyhow!("No time records found"))?;
+    let distances = distances.ok_or_else(|| anyhow!("No distance records found"))?;
+    if times.len() != distances.len() {
+        bail!(
+            "Sheet has {} times, but {} distances",
+            times.len(),
+            distances.len()
+        );
+    }
+    Ok(times
+        .into_iter()
+        .zip(distances)
+        .map(|(time, distance)| Race::new(time, distance))
+        .collect())
+}
+
+fn overflow() -> anyhow::Error {
+    anyhow!("Arithmetic overflow, consider using a wider number type")
+}
+
+#[cfg(test)]
+mod tests {
+    use num::BigUint;
+
+    use super::*;
+
+    #[test]
+    fn winning_intervals() {
+        let rate = ChargeRate::unit();
+        assert_eq!(
+            Some(2..=5),
+            Race::new(7u64, 9).winning_hold_times(&rate).unwrap()
+        );
+        assert_eq!(
+            Some(4..=11),
+            Race::new(15u64, 40).winning_hold_times(&rate).unwrap()
+        );
+        assert_eq!(
+            Some(11..=19),
+            Race::new(30u64, 200).winning_hold_times(&rate).unwrap()
+        );
+        assert_eq!(None, Race::new(4u64, 4).winning_hold_times(&rate).unwrap());
+        assert_eq!(
+            71503,
+            Race::new(71530u64, 940200).num_ways_to_win(&rate).unwrap()
+        );
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        for (speed, per) in [(1u64, 1u64), (2, 1), (3, 2), (1, 3)] {
+            let rate = ChargeRate { speed, per };
+            for time in 0..40u64 {
+                for distance in 0..120u64 {
+                    let race = Race::new(time, distance);
+                    let expected = (0..=time)
+                        .filter(|hold| race.wins(hold, &rate).unwrap())
+                        .count() as u64;
+                    assert_eq!(expected, race.num_ways_to_win(&rate).unwrap());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn wide_numbers() {
+        // far beyond the precision of f64
+        let time: u128 = (1 << 60) + 12345;
+        let distance: u128 = (1 << 118) + 987654321;
+        let race = Race::new(time, distance);
+        let hold_times = race
+            .winning_hold_times(&ChargeRate::unit())
+            .unwrap()
+            .unwrap();
+        assert!(race.wins(hold_times.start(), &ChargeRate::unit()).unwrap());
+        assert!(!race
+            .wins(&(hold_times.start() - 1), &ChargeRate::unit())
+            .unwrap());
+
+        // the same race overflows u64, but can be solved with big integers
+        assert!(Race::new(time as u64, 1)
+            .winning_hold_times(&ChargeRate::unit())
+            .is_err());
+        let big_race = Race::new(BigUint::from(time), BigUint::from(distance));
+        let big_ways = big_race.num_ways_to_win(&ChargeRate::unit()).unwrap();
+        assert_eq!(
+            BigUint::from(race.num_ways_to_win(&ChargeRate::unit()).unwrap()),
+            big_ways
+        );
+    }
+
+    #[test]
+    fn race_sheets() {
+        let sheet = r#"
+            Time:      7  15   30  12
+            Distance:  9  40  200  20
+        "#;
+        let races: Vec<Race<u64>> = parse_sheet(sheet, Kerning::Unkerned).unwrap();
+        assert_eq!(4, races.len());
+        assert_eq!(Race::new(12, 20), races[3]);
+
+        let races: Vec<Race<u64>> = parse_sheet(sheet, Kerning::Kerned).unwrap();
+        assert_eq!(vec![Race::new(7153012, 94020020)], races);
+
+        let uneven = "Time: 7 15\nDistance: 9";
+        assert!(parse_sheet::<u64>(uneven, Kerning::Unkerned).is_err());
+    }
+}
@@ -0,0 +1,473 @@
+use anyhow::{anyhow, bail, Result};
+use num::Integer;
+
+use crate::{ChargeRate, Race};
+
+// the exact margin optimiser looks at every subset of races
+const MAX_RACES: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Objective {
+    // win as many races as possible, ties are broken by the total margin
+    RacesWon,
+    // maximise the sum of distances by which the records are beaten
+    TotalMargin,
+}
+
+// Boats with different charge rates travel fractions of millimeters, so margins are counted in
+// units of 1 / `margin_scale` millimeters, where the scale is the least common multiple of the
+// `per` values of all charge rates. With the original boats it is 1.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Allocation {
+    // hold time for every race, zero for the races that are given up
+    pub holds: Vec<u64>,
+    pub races_won: usize,
+    pub total_margin: u128,
+    pub margin_scale: u128,
+}
+
+// A set of races that are sailed with a shared charging budget: the sum of hold times over all
+// races cannot exceed `budget`. Each boat may additionally be limited to a maximum hold time, and
+// charge at its own rate. The limits and rates are kept next to the races and can only be set
+// for races that exist.
+#[derive(Debug, Clone)]
+pub struct Regatta {
+    races: Vec<Race<u64>>,
+    budget: Option<u64>,
+    max_holds: Vec<Option<u64>>,
+    rates: Vec<ChargeRate<u64>>,
+}
+
+// what can be gained in a single race
+#[derive(Debug, Clone, Copy)]
+struct Prospect {
+    // the shortest winning hold time
+    min_hold: u64,
+    // the hold time after which waiting longer does not help anymore
+    max_hold: u64,
+}
+
+impl Regatta {
+    pub fn new(races: Vec<Race<u64>>) -> Self {
+        let max_holds = vec![None; races.len()];
+        let rates = vec![ChargeRate::unit(); races.len()];
+        Self {
+            races,
+            budget: None,
+            max_holds,
+            rates,
+        }
+    }
+
+    pub fn races(&self) -> &[Race<u64>] {
+        &self.races
+    }
+
+    pub fn budget(&self) -> Option<u64> {
+        self.budget
+    }
+
+    pub fn with_budget(mut self, budget: u64) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    pub fn with_max_hold(mut self, race: usize, max_hold: u64) -> Result<Self> {
+        let Some(slot) = self.max_holds.get_mut(race) else {
+            bail!("No race {race}, the regatta has {} races", self.races.len());
+        };
+        *slot = Some(max_hold);
+        Ok(self)
+    }
+
+    pub fn with_rate(mut self, race: usize, rate: ChargeRate<u64>) -> Result<Self> {
+        if rate.speed == 0 || rate.per == 0 {
+            bail!("Charge rate must be positive");
+        }
+        let Some(slot) = self.rates.get_mut(race) else {
+            bail!("No race {race}, the regatta has {} races", self.races.len());
+        };
+        *slot = rate;
+        Ok(self)
+    }
+
+    pub fn optimise(&self, objective: Objective) -> Result<Allocation> {
+        let prospects = self.prospects()?;
+        let scale = self.margin_scale()?;
+        let budget = self.budget.unwrap_or(u64::MAX);
+        let winnable: Vec<usize> = (0..self.races.len())
+            .filter(|&i| prospects[i].is_some())
+            .collect();
+
+        match objective {
+            // winning a race has unit value, so picking the cheapest races first is optimal; the
+            // exact search is only needed to break ties by the total margin
+            Objective::RacesWon if winnable.len() > MAX_RACES => {
+                let mut by_cost = winnable;
+                by_cost.sort_by_key(|&i| prospects[i].map(|p| p.min_hold));
+                let mut spent: u64 = 0;
+                let mut entered: Vec<usize> = Vec::new();
+                for i in by_cost {
+                    let cost = prospects[i].map_or(0, |p| p.min_hold);
+                    if spent.saturating_add(cost) > budget {
+                        break;
+                    }
+                    spent += cost;
+                    entered.push(i);
+                }
+                self.allocate(&entered, &prospects, budget, scale)
+            }
+            Objective::RacesWon => self.best_allocation(
+                self.subsets(&winnable, &prospects, budget)?,
+                &prospects,
+                budget,
+                scale,
+                |allocation| (allocation.races_won, allocation.total_margin),
+            ),
+            Objective::TotalMargin => self.best_allocation(
+                self.subsets(&winnable, &prospects, budget)?,
+                &prospects,
+                budget,
+                scale,
+                |allocation| (allocation.total_margin, allocation.races_won),
+            ),
+        }
+    }
+
+    fn prospects(&self) -> Result<Vec<Option<Prospect>>> {
+        self.races
+            .iter()
+            .zip(self.max_holds.iter())
+            .zip(self.rates.iter())
+            .map(|((race, max_hold), rate)| {
+                let Some(hold_times) = race.winning_hold_times(rate)? else {
+                    return Ok(None);
+                };
+                let limit = max_hold.unwrap_or(u64::MAX);
+                let prospect = Prospect {
+                    min_hold: *hold_times.start(),
+                    max_hold: limit.min(race.time / 2),
+                };
+                Ok(Some(prospect).filter(|p| p.min_hold <= p.max_hold))
+            })
+            .collect()
+    }
+
+    // least common multiple of the `per` values of all charge rates
+    fn margin_scale(&self) -> Result<u128> {
+        self.rates.iter().try_fold(1u128, |scale, rate| {
+            let per = rate.per as u128;
+            (scale / scale.gcd(&per))
+                .checked_mul(per)
+                .ok_or_else(|| anyhow!("Charge rates have no common unit that fits into u128"))
+        })
+    }
+
+    // the best allocation over the given subsets of races, the last one on ties
+    fn best_allocation<K: Ord>(
+        &self,
+        subsets: impl Iterator<Item = Vec<usize>>,
+        prospects: &[Option<Prospect>],
+        budget: u64,
+        scale: u128,
+        key: impl Fn(&Allocation) -> K,
+    ) -> Result<Allocation> {
+        let mut best = Allocation {
+            holds: vec![0; self.races.len()],
+            margin_scale: scale,
+            ..Default::default()
+        };
+        for subset in subsets {
+            let allocation = self.allocate(&subset, prospects, budget, scale)?;
+            if key(&allocation) >= key(&best) {
+                best = allocation;
+            }
+        }
+        Ok(best)
+    }
+
+    // all subsets of races whose minimal winning hold times fit into the budget
+    fn subsets<'a>(
+        &self,
+        races: &'a [usize],
+        prospects: &'a [Option<Prospect>],
+        budget: u64,
+    ) -> Result<impl Iterator<Item = Vec<usize>> + 'a> {
+        if races.len() > MAX_RACES {
+            bail!(
+                "Too many races for the exact optimiser: {}, at most {MAX_RACES} are supported",
+                races.len()
+            );
+        }
+        Ok((0u32..(1 << races.len())).filter_map(move |mask| {
+            let subset: Vec<usize> = (0..races.len())
+                .filter(|bit| mask & (1 << bit) != 0)
+                .map(|bit| races[bit])
+                .collect();
+            let cost = subset
+                .iter()
+                .filter_map(|&i| prospects[i])
+                .try_fold(0u64, |acc, p| acc.checked_add(p.min_hold))?;
+            (cost <= budget).then_some(subset)
+        }))
+    }
+
+    // Enters the given races with their minimal winning hold times, then spends the rest of the
+    // budget where it gains the most distance. In race `i`, holding one millisecond longer at `h`
+    // gains `c_i * (time - 2h - 1)` units of margin, where `c_i = speed * scale / per`. The gains
+    // only decrease, so the remaining budget goes to all steps that gain at least some threshold,
+    // found by binary search.
+    fn allocate(
+        &self,
+        entered: &[usize],
+        prospects: &[Option<Prospect>],
+        budget: u64,
+        scale: u128,
+    ) -> Result<Allocation> {
+        let unwinnable = || anyhow!("Entered a race that cannot be won");
+        let mut holds = vec![0u64; self.races.len()];
+        let mut remaining = budget;
+        for &i in entered {
+            let prospect = prospects[i].ok_or_else(unwinnable)?;
+            holds[i] = prospect.min_hold;
+            remaining = remaining
+                .checked_sub(prospect.min_hold)
+                .ok_or_else(|| anyhow!("Entered races do not fit into the budget"))?;
+        }
+
+        // units of margin per millimeter travelled in every race
+        let factors: Vec<u128> = self
+            .rates
+            .iter()
+            .map(|rate| {
+                (scale / rate.per as u128)
+                    .checked_mul(rate.speed as u128)
+                    .ok_or_else(|| anyhow!("Charge rate {rate:?} is too fast to measure margins"))
+            })
+            .collect::<Result<_>>()?;
+        let factor = |i: usize| factors[i];
+        // number of extra steps in race `i` that gain at least `gain` units each
+        let steps = |i: usize, gain: u128| -> u64 {
+            let Some(prospect) = prospects[i] else {
+                return 0;
+            };
+            if prospect.min_hold == prospect.max_hold {
+                return 0;
+            }
+            let first = (self.races[i].time - 2 * prospect.min_hold - 1) as u128;
+            let needed = gain.div_ceil(factor(i));
+            if first < needed {
+                return 0;
+            }
+            let steps = u64::try_from((first - needed) / 2 + 1).unwrap_or(u64::MAX);
+            steps.min(prospect.max_hold - prospect.min_hold)
+        };
+        let total_steps = |gain: u128| -> u64 {
+            entered
+                .iter()
+                .fold(0u64, |acc, &i| acc.saturating_add(steps(i, gain)))
+        };
+
+        // the smallest threshold that does not exceed the remaining budget
+        let (mut low, mut high) = (
+            1u128,
+            entered
+                .iter()
+                .map(|&i| factor(i).saturating_mul(self.races[i].time as u128))
+                .max()
+                .unwrap_or(0)
+                .saturating_add(1),
+        );
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if total_steps(mid) <= remaining {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+        for &i in entered {
+            let taken = steps(i, low);
+            holds[i] += taken;
+            remaining -= taken;
+        }
+
+        // whatever is left is spent on the steps just below the threshold
+        if low > 1 {
+            for &i in entered {
+                let extra = (steps(i, low - 1) - steps(i, low)).min(remaining);
+                holds[i] += extra;
+                remaining -= extra;
+            }
+        }
+
+        let mut allocation = Allocation {
+            holds,
+            margin_scale: scale,
+            ..Default::default()
+        };
+        let overflow = || anyhow!("Margins do not fit into u128");
+        for (i, race) in self.races.iter().enumerate() {
+            let hold = allocation.holds[i];
+            let travelled = factor(i)
+                .checked_mul(hold as u128)
+                .and_then(|t| t.checked_mul((race.time - hold.min(race.time)) as u128))
+                .ok_or_else(overflow)?;
+            let record = scale
+                .checked_mul(race.distance as u128)
+                .ok_or_else(overflow)?;
+            if travelled > record {
+                allocation.races_won += 1;
+                allocation.total_margin = allocation
+                    .total_margin
+                    .checked_add(travelled - record)
+                    .ok_or_else(overflow)?;
+            }
+        }
+        Ok(allocation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> Vec<Race<u64>> {
+        vec![Race::new(7, 9), Race::new(15, 40), Race::new(30, 200)]
+    }
+
+    #[test]
+    fn shared_budget() {
+        let regatta = Regatta::new(example()).with_budget(6);
+        let most_wins = regatta.optimise(Objective::RacesWon).unwrap();
+        assert_eq!(vec![2, 4, 0], most_wins.holds);
+        assert_eq!(2, most_wins.races_won);
+        assert_eq!(5, most_wins.total_margin);
+
+        let best_margin = regatta.optimise(Objective::TotalMargin).unwrap();
+        assert_eq!(vec![0, 6, 0], best_margin.holds);
+        assert_eq!(14, best_margin.total_margin);
+
+        let unlimited = Regatta::new(example())
+            .optimise(Objective::RacesWon)
+            .unwrap();
+        assert_eq!(vec![3, 7, 15], unlimited.holds);
+    }
+
+    #[test]
+    fn charging_constraint() {
+        let regatta = Regatta::new(example()).with_max_hold(2, 10).unwrap();
+        let allocation = regatta.optimise(Objective::RacesWon).unwrap();
+        assert_eq!(2, allocation.races_won);
+        assert_eq!(0, allocation.holds[2]);
+        assert!(Regatta::new(example()).with_max_hold(3, 10).is_err());
+    }
+
+    #[test]
+    fn charge_rates() {
+        // a boat charging twice as fast wins the first race with a single millisecond
+        let regatta = Regatta::new(example()).with_budget(1);
+        assert_eq!(0, regatta.optimise(Objective::RacesWon).unwrap().races_won);
+        let fast = ChargeRate { speed: 2, per: 1 };
+        let regatta = regatta.with_rate(0, fast.clone()).unwrap();
+        let allocation = regatta.optimise(Objective::RacesWon).unwrap();
+        assert_eq!(vec![1, 0, 0], allocation.holds);
+        assert_eq!(1, allocation.margin_scale);
+        assert_eq!(2 * 6 - 9, allocation.total_margin);
+
+        // slow boats travel half millimeters
+        let slow = ChargeRate { speed: 1, per: 2 };
+        let regatta = Regatta::new(vec![Race::new(9, 10), Race::new(12, 15), Race::new(14, 40)])
+            .with_rate(1, slow.clone())
+            .unwrap()
+            .with_rate(2, fast.clone())
+            .unwrap();
+        let rates = [ChargeRate::unit(), slow, fast];
+        for budget in 0..25 {
+            let mut best = 0u128;
+            for a in 0..=9u64 {
+                for b in 0..=12u64 {
+                    for c in 0..=14u64 {
+                        if a + b + c > budget {
+                            continue;
+                        }
+                        let margin: u128 = regatta
+                            .races()
+                            .iter()
+                            .zip(&rates)
+                            .zip([a, b, c])
+                            .map(|((race, rate), hold)| {
+                                let travelled =
+                                    2 / rate.per * rate.speed * hold * (race.time - hold);
+                                (travelled as u128).saturating_sub(2 * race.distance as u128)
+                            })
+                            .sum();
+                        best = best.max(margin);
+                    }
+                }
+            }
+            let allocation = regatta
+                .clone()
+                .with_budget(budget)
+                .optimise(Objective::TotalMargin)
+                .unwrap();
+            assert_eq!(2, allocation.margin_scale);
+            assert_eq!(best, allocation.total_margin);
+        }
+        assert!(regatta
+            .clone()
+            .with_rate(0, ChargeRate { speed: 0, per: 1 })
+            .is_err());
+
+        // boats measured in very different units make margins too fine for u128
+        let races = vec![Race::new(1 << 31, 0); 3];
+        let pers = [4_294_967_291, 4_294_967_279, (1 << 40) + 1];
+        let regatta = (0..3).fold(Regatta::new(races), |regatta, i| {
+            let rate = ChargeRate {
+                speed: 1,
+                per: pers[i],
+            };
+            regatta.with_rate(i, rate).unwrap()
+        });
+        let error = regatta.optimise(Objective::TotalMargin).unwrap_err();
+        assert_eq!("Margins do not fit into u128", error.to_string());
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let races = vec![Race::new(9, 10), Race::new(12, 20), Race::new(14, 40)];
+        for budget in 0..25 {
+            let mut best_margin = 0;
+            let mut best_wins = 0;
+            for a in 0..=9u64 {
+                for b in 0..=12u64 {
+                    for c in 0..=14u64 {
+                        if a + b + c > budget {
+                            continue;
+                        }
+                        let (wins, margin) = races.iter().zip([a, b, c]).fold(
+                            (0, 0u128),
+                            |(wins, margin), (race, hold)| {
+                                let travelled = hold * (race.time - hold);
+                                if travelled > race.distance {
+                                    (wins + 1, margin + (travelled - race.distance) as u128)
+                                } else {
+                                    (wins, margin)
+                                }
+                            },
+                        );
+                        best_margin = best_margin.max(margin);
+                        best_wins = best_wins.max(wins);
+                    }
+                }
+            }
+
+            let regatta = Regatta::new(races.clone()).with_budget(budget);
+            let allocation = regatta.optimise(Objective::TotalMargin).unwrap();
+            assert_eq!(best_margin, allocation.total_margin);
+            assert!(allocation.holds.iter().sum::<u64>() <= budget);
+            let allocation = regatta.optimise(Objective::RacesWon).unwrap();
+            assert_eq!(best_wins, allocation.races_won);
+        }
+    }
+}
@@ -0,0 +1,394 @@
+use std::{cmp::Ordering, collections::HashMap};
+
+use anyhow::{anyhow, bail, Result};
+
+pub mod verify;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HandType {
+    HighCard,
+    OnePair,
+    TwoPair,
+    ThreeOfAKind,
+    Straight,
+    Flush,
+    FullHouse,
+    FourOfAKind,
+    StraightFlush,
+    FiveOfAKind,
+}
+
+impl HandType {
+    // the type of a hand given the sizes of its groups of equal cards, sorted from largest
+    fn from_groups(groups: &[usize]) -> Self {
+        let largest = groups.first().copied().unwrap_or(0);
+        let second = groups.get(1).copied().unwrap_or(0);
+        match (largest, second) {
+            (l, _) if l >= 5 => HandType::FiveOfAKind,
+            (4, _) => HandType::FourOfAKind,
+            (3, s) if s >= 2 => HandType::FullHouse,
+            (3, _) => HandType::ThreeOfAKind,
+            (2, 2) => HandType::TwoPair,
+            (2, _) => HandType::OnePair,
+            _ => HandType::HighCard,
+        }
+    }
+}
+
+// how hands of the same type are ordered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TieBreak {
+    // compare the cards one by one in the order they were dealt
+    CardOrder,
+    // compare the largest groups first, groups of the same size by their rank (standard poker)
+    GroupedRanks,
+    // compare the cards from the strongest to the weakest
+    HighestCards,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Card {
+    pub face: char,
+    // position of the face in the card order of the rule set, higher is stronger
+    pub rank: usize,
+    pub suit: Option<char>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hand {
+    pub cards: Vec<Card>,
+    pub bid: usize,
+    pub hand_type: HandType,
+}
+
+#[derive(Debug, Clone)]
+pub struct RuleSet {
+    // card faces from the weakest to the strongest
+    pub card_order: Vec<char>,
+    // faces that act like whatever card makes the hand the strongest
+    pub wildcards: Vec<char>,
+    pub hand_size: usize,
+    pub tie_break: TieBreak,
+    // cards carry a suit and straights and flushes count as hand types
+    pub poker: bool,
+}
+
+impl RuleSet {
+    pub fn camel() -> Self {
+        Self {
+            card_order: "23456789TJQKA".chars().collect(),
+            wildcards: Vec::new(),
+            hand_size: 5,
+            tie_break: TieBreak::CardOrder,
+            poker: false,
+        }
+    }
+
+    // jokers are wildcards, but the weakest individual cards
+    pub fn camel_with_jokers() -> Self {
+        Self {
+            card_order: "J23456789TQKA".chars().collect(),
+            wildcards: vec!['J'],
+            ..Self::camel()
+        }
+    }
+
+    // hands are written as space separated face and suit pairs, e.g. `TH JH QH KH AH 10`
+    pub fn poker() -> Self {
+        Self {
+            tie_break: TieBreak::GroupedRanks,
+            poker: true,
+            ..Self::camel()
+        }
+    }
+
+    pub fn with_wildcards(mut self, wildcards: &str) -> Self {
+        self.wildcards = wildcards.chars().collect();
+        self
+    }
+
+    pub fn with_hand_size(mut self, hand_size: usize) -> Self {
+        self.hand_size = hand_size;
+        self
+    }
+
+    pub fn with_tie_break(mut self, tie_break: TieBreak) -> Self {
+        self.tie_break = tie_break;
+        self
+    }
+
+    pub fn card(&self, face: char, suit: Option<char>) -> Result<Card> {
+        let rank = self
+            .card_order
+            .iter()
+            .position(|&c| c == face)
+            .ok_or_else(|| anyhow!("Unrecognized card: {face}"))?;
+        Ok(Card { face, rank, suit })
+    }
+
+    pub fn is_wildcard(&self, card: &Card) -> bool {
+        self.wildcards.contains(&card.face)
+    }
+
+    pub fn parse_cards(&self, s: &str) -> Result<Vec<Card>> {
+        let cards = if self.poker {
+            s.split_whitespace()
+                .map(|token| {
+                    let mut chars = token.chars();
+                    match (chars.next(), chars.next(), chars.next()) {
+                        (Some(face), Some(suit), None) => self.card(face, Some(suit)),
+                        _ => bail!("Could not parse a card: {token}"),
+                    }
+                })
+                .collect::<Result<Vec<Card>>>()?
+        } else {
+            s.chars()
+                .filter(|c| !c.is_whitespace())
+                .map(|c| self.card(c, None))
+                .collect::<Result<Vec<Card>>>()?
+        };
+
+        if cards.len() != self.hand_size {
+            bail!(
+                "Expected {} cards, found {}: {s}",
+                self.hand_size,
+                cards.len()
+            );
+        }
+        Ok(cards)
+    }
+
+    pub fn parse_hand(&self, s: &str) -> Result<Hand> {
+        let (cards_str, bid_str) = s
+            .trim()
+            .rsplit_once(' ')
+            .ok_or_else(|| anyhow!("Could not parse a Hand: {s}"))?;
+        let bid = bid_str.parse::<usize>()?;
+        let cards = self.parse_cards(cards_str)?;
+        let hand_type = self.hand_type(&cards);
+        Ok(Hand {
+            cards,
+            bid,
+            hand_type,
+        })
+    }
+
+    pub fn hand_type(&self, cards: &[Card]) -> HandType {
+        let mut wildcards = 0;
+        let mut counts: HashMap<usize, usize> = HashMap::new();
+        for card in cards.iter() {
+            if self.is_wildcard(card) {
+                wildcards += 1;
+            } else {
+                *counts.entry(card.rank).or_insert(0) += 1;
+            }
+        }
+
+        // wildcards always do best by joining the largest group
+        let mut groups: Vec<usize> = counts.into_values().collect();
+        groups.sort_by(|a, b| b.cmp(a));
+        match groups.first_mut() {
+            Some(largest) => *largest += wildcards,
+            None => groups.push(wildcards),
+        }
+        let by_groups = HandType::from_groups(&groups);
+        if !self.poker {
+            return by_groups;
+        }
+
+        let straight = self.is_straight(cards);
+        let flush = self.is_flush(cards);
+        let by_sequence = match (straight, flush) {
+            (true, true) => HandType::StraightFlush,
+            (false, true) => HandType::Flush,
+            (true, false) => HandType::Straight,
+            (false, false) => HandType::HighCard,
+        };
+        by_groups.max(by_sequence)
+    }
+
+    // the cards can be arranged into consecutive ranks, the strongest card may also be used
+    // below the weakest one (like the ace in A2345)
+    fn is_straight(&self, cards: &[Card]) -> bool {
+        let mut ranks: Vec<usize> = cards
+            .iter()
+            .filter(|c| !self.is_wildcard(c))
+            .map(|c| c.rank)
+            .collect();
+        ranks.sort();
+        if ranks.windows(2).any(|w| w[0] == w[1]) {
+            return false;
+        }
+        let fits = |ranks: &[usize]| match (ranks.first(), ranks.last()) {
+            (Some(low), Some(high)) => high - low < cards.len(),
+            _ => true,
+        };
+        if fits(&ranks) {
+            return true;
+        }
+
+        // move the strongest card below the weakest one
+        let top = self.card_order.len() - 1;
+        if ranks.last() == Some(&top) {
+            let mut low_ranks: Vec<usize> = vec![0];
+            low_ranks.extend(ranks[..ranks.len() - 1].iter().map(|r| r + 1));
+            return fits(&low_ranks);
+        }
+        false
+    }
+
+    fn is_flush(&self, cards: &[Card]) -> bool {
+        let mut suits = cards
+            .iter()
+            .filter(|c| !self.is_wildcard(c))
+            .map(|c| c.suit);
+        match suits.next() {
+            Some(first) => first.is_some() && suits.all(|s| s == first),
+            None => true,
+        }
+    }
+
+    // ranks compared one by one when two hands have the same type, shifted by one to leave room
+    // for a strongest card that is played as the weakest one
+    fn tie_break_key(&self, hand: &Hand) -> Vec<usize> {
+        let mut ranks: Vec<usize> = hand.cards.iter().map(|c| c.rank + 1).collect();
+        match self.tie_break {
+            TieBreak::CardOrder => ranks,
+            TieBreak::HighestCards => {
+                ranks.sort_by(|a, b| b.cmp(a));
+                ranks
+            }
+            TieBreak::GroupedRanks => {
+                let top = self.card_order.len();
+                let is_wheel =
+                    matches!(hand.hand_type, HandType::Straight | HandType::StraightFlush)
+                        && ranks.contains(&top)
+                        && ranks.contains(&1);
+                if is_wheel {
+                    // the strongest card counts as the weakest one in a low straight
+                    ranks.iter_mut().filter(|r| **r == top).for_each(|r| *r = 0);
+                }
+
+                let mut counts: HashMap<usize, usize> = HashMap::new();
+                ranks
+                    .iter()
+                    .for_each(|r| *counts.entry(*r).or_insert(0) += 1);
+                let mut groups: Vec<(usize, usize)> = counts.into_iter().collect();
+                groups.sort_by(|a, b| b.1.cmp(&a.1).then(b.0.cmp(&a.0)));
+                groups
+                    .into_iter()
+                    .flat_map(|(rank, count)| std::iter::repeat_n(rank, count))
+                    .collect()
+            }
+        }
+    }
+
+    pub fn compare(&self, a: &Hand, b: &Hand) -> Ordering {
+        a.hand_type
+            .cmp(&b.hand_type)
+            .then_with(|| self.tie_break_key(a).cmp(&self.tie_break_key(b)))
+    }
+
+    // every hand wins its bid multiplied by its rank among all hands
+    pub fn total_winnings(&self, input: &str) -> Result<usize> {
+        let mut hands: Vec<Hand> = input
+            .lines()
+            .filter_map(|l| match l.trim() {
+                trimmed if !trimmed.is_empty() => Some(self.parse_hand(trimmed)),
+                _ => None,
+            })
+            .collect::<Result<Vec<Hand>>>()?;
+        hands.sort_by(|a, b| self.compare(a, b));
+
+        Ok(hands
+            .into_iter()
+            .enumerate()
+            .fold(0, |sum, (idx, hand)| sum + (idx + 1) * hand.bid))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = r#"
+        32T3K 765
+        T55J5 684
+        KK677 28
+        KTJJT 220
+        QQQJA 483
+    "#;
+
+    #[test]
+    fn presets() {
+        assert_eq!(6440, RuleSet::camel().total_winnings(EXAMPLE).unwrap());
+        assert_eq!(
+            5905,
+            RuleSet::camel_with_jokers()
+                .total_winnings(EXAMPLE)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn wildcards_and_hand_sizes() {
+        let rules = RuleSet::camel().with_wildcards("JQ");
+        let cards = rules.parse_cards("JQ2KA").unwrap();
+        assert_eq!(HandType::ThreeOfAKind, rules.hand_type(&cards));
+
+        let rules = RuleSet::camel_with_jokers().with_hand_size(3);
+        let cards = rules.parse_cards("2J2").unwrap();
+        assert_eq!(HandType::ThreeOfAKind, rules.hand_type(&cards));
+        assert!(rules.parse_cards("2J22").is_err());
+
+        let rules = RuleSet::camel().with_hand_size(7);
+        let cards = rules.parse_cards("2223334").unwrap();
+        assert_eq!(HandType::FullHouse, rules.hand_type(&cards));
+    }
+
+    #[test]
+    fn poker_ranking() {
+        let rules = RuleSet::poker();
+        let hand_type = |s: &str| rules.hand_type(&rules.parse_cards(s).unwrap());
+        assert_eq!(HandType::Straight, hand_type("9H TD JS QC KD"));
+        assert_eq!(HandType::Straight, hand_type("AH 2D 3S 4C 5D"));
+        assert_eq!(HandType::HighCard, hand_type("QH KD AS 2C 3D"));
+        assert_eq!(HandType::Flush, hand_type("2H 7H 9H JH KH"));
+        assert_eq!(HandType::StraightFlush, hand_type("TS JS QS KS AS"));
+        assert_eq!(HandType::FullHouse, hand_type("2H 2D 2S KC KD"));
+
+        let input = r#"
+            AH 2D 3S 4C 5D 1
+            2H 3D 4S 5C 6D 10
+            KH KD 7S 7C 2D 100
+            KS KC 7H 7D 3C 1000
+        "#;
+        assert_eq!(
+            100 + 2 * 1000 + 3 + 4 * 10,
+            rules.total_winnings(input).unwrap()
+        );
+    }
+
+    #[test]
+    fn tie_breaks() {
+        let input = r#"
+            2AAKK 1
+            AAKK3 10
+        "#;
+        let rules = RuleSet::camel();
+        assert_eq!(1 + 2 * 10, rules.total_winnings(input).unwrap());
+        let rules = RuleSet::camel().with_tie_break(TieBreak::GroupedRanks);
+        assert_eq!(1 + 2 * 10, rules.total_winnings(input).unwrap());
+        let rules = RuleSet::camel().with_tie_break(TieBreak::HighestCards);
+        assert_eq!(1 + 2 * 10, rules.total_winnings(input).unwrap());
+
+        let input = r#"
+            AAKK2 1
+            KKAA3 10
+        "#;
+        let rules = RuleSet::camel();
+        assert_eq!(2 + 10, rules.total_winnings(input).unwrap());
+        let rules = RuleSet::camel().with_tie_break(TieBreak::GroupedRanks);
+        assert_eq!(1 + 2 * 10, rules.total_winnings(input).unwrap());
+    }
+}
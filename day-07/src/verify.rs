@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Result};
+
+use crate::{Card, HandType, RuleSet};
+
+// suits a wildcard can take in suited rule sets
+const SUITS: [char; 4] = ['C', 'D', 'H', 'S'];
+
+// a hand with every wildcard replaced by a concrete card
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Substitution {
+    pub cards: Vec<Card>,
+    pub hand_type: HandType,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    pub cards: Vec<Card>,
+    pub classified: HandType,
+    pub best: Substitution,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Verification {
+    pub hands_checked: usize,
+    pub mismatches: Vec<Mismatch>,
+}
+
+impl RuleSet {
+    // Tries every possible replacement of the wildcards and returns the strongest resulting hand.
+    // Among equally strong replacements the first one in card order wins.
+    pub fn best_substitution(&self, cards: &[Card]) -> Substitution {
+        let natural = RuleSet {
+            wildcards: Vec::new(),
+            ..self.clone()
+        };
+        // a wildcard may also stand for its own face played as a natural card
+        let candidates: Vec<Card> = self
+            .card_order
+            .iter()
+            .enumerate()
+            .flat_map(|(rank, &face)| {
+                let suits: Vec<Option<char>> = if self.poker {
+                    SUITS.iter().map(|&s| Some(s)).collect()
+                } else {
+                    vec![None]
+                };
+                suits.into_iter().map(move |suit| Card { face, rank, suit })
+            })
+            .collect();
+
+        let mut best = Substitution {
+            cards: cards.to_vec(),
+            hand_type: natural.hand_type(cards),
+        };
+        let mut current = cards.to_vec();
+        self.substitute(&natural, &candidates, &mut current, 0, 0, &mut best);
+        best
+    }
+
+    fn substitute(
+        &self,
+        natural: &RuleSet,
+        candidates: &[Card],
+        current: &mut Vec<Card>,
+        position: usize,
+        first_candidate: usize,
+        best: &mut Substitution,
+    ) {
+        let Some(next) = (position..current.len()).find(|&i| self.is_wildcard(&current[i])) else {
+            let hand_type = natural.hand_type(current);
+            if hand_type > best.hand_type {
+                *best = Substitution {
+                    cards: current.clone(),
+                    hand_type,
+                };
+            }
+            return;
+        };
+
+        // the order in which wildcards are replaced does not change the hand type, so only
+        // non-decreasing sequences of candidates need to be tried
+        let wildcard = current[next];
+        for (idx, candidate) in candidates.iter().enumerate().skip(first_candidate) {
+            current[next] = *candidate;
+            self.substitute(natural, candidates, current, next + 1, idx, best);
+        }
+        current[next] = wildcard;
+    }
+
+    // compares the fast classifier with the brute force one on every possible hand
+    pub fn verify(&self) -> Result<Verification> {
+        if self.poker {
+            bail!("Only unsuited rule sets can be verified exhaustively");
+        }
+
+        let mut verification = Verification::default();
+        for (cards, best) in self.all_substitutions() {
+            let classified = self.hand_type(&cards);
+            verification.hands_checked += 1;
+            if classified != best.hand_type {
+                verification.mismatches.push(Mismatch {
+                    cards,
+                    classified,
+                    best,
+                });
+            }
+        }
+        Ok(verification)
+    }
+
+    // Every hand of unsuited cards together with its best substitution. The substitution only
+    // depends on which cards are in the hand, so it is searched once per set of cards, and the
+    // same replacements are put in the places of the wildcards of every other order.
+    fn all_substitutions(&self) -> impl Iterator<Item = (Vec<Card>, Substitution)> + '_ {
+        let faces = self.card_order.len();
+        let mut known: HashMap<Vec<usize>, (Vec<Card>, HandType)> = HashMap::new();
+        (0..faces.pow(self.hand_size as u32)).map(move |index| {
+            let cards: Vec<Card> = (0..self.hand_size)
+                .map(|i| {
+                    let rank = index / faces.pow(i as u32) % faces;
+                    Card {
+                        face: self.card_order[rank],
+                        rank,
+                        suit: None,
+                    }
+                })
+                .collect();
+
+            let mut key: Vec<usize> = cards.iter().map(|c| c.rank).collect();
+            key.sort();
+            let (replacements, hand_type) = known.entry(key).or_insert_with(|| {
+                let best = self.best_substitution(&cards);
+                let replacements = cards
+                    .iter()
+                    .zip(best.cards)
+                    .filter(|(card, _)| self.is_wildcard(card))
+                    .map(|(_, replacement)| replacement)
+                    .collect();
+                (replacements, best.hand_type)
+            });
+
+            let mut replacements = replacements.iter();
+            let substituted = cards
+                .iter()
+                .map(|card| {
+                    if self.is_wildcard(card) {
+                        *replacements.next().unwrap_or(card)
+                    } else {
+                        *card
+                    }
+                })
+                .collect();
+            let best = Substitution {
+                cards: substituted,
+                hand_type: *hand_type,
+            };
+            (cards, best)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn joker_classifier_is_exact() {
+        let verification = RuleSet::camel_with_jokers().verify().unwrap();
+        assert_eq!(13usize.pow(5), verification.hands_checked);
+        assert_eq!(Vec::<Mismatch>::new(), verification.mismatches);
+
+        let verification = RuleSet::camel().with_wildcards("JQ").verify().unwrap();
+        assert_eq!(Vec::<Mismatch>::new(), verification.mismatches);
+    }
+
+    #[test]
+    fn substitution_report() {
+        let rules = RuleSet::camel_with_jokers();
+        let best = rules.best_substitution(&rules.parse_cards("KTJJT").unwrap());
+        assert_eq!(HandType::FourOfAKind, best.hand_type);
+        assert_eq!(rules.parse_cards("KTTTT").unwrap(), best.cards);
+
+        let best = rules.best_substitution(&rules.parse_cards("32T3K").unwrap());
+        assert_eq!(HandType::OnePair, best.hand_type);
+        assert_eq!(rules.parse_cards("32T3K").unwrap(), best.cards);
+
+        let rules = RuleSet::poker().with_wildcards("J");
+        let best = rules.best_substitution(&rules.parse_cards("TS JD QS KS AS").unwrap());
+        assert_eq!(HandType::StraightFlush, best.hand_type);
+        assert_eq!(rules.card('J', Some('S')).unwrap(), best.cards[1]);
+    }
+
+    #[test]
+    fn reused_substitutions_follow_the_hand() {
+        let rules = RuleSet::camel().with_wildcards("JQ").with_hand_size(3);
+        for (cards, best) in rules.all_substitutions() {
+            assert_eq!(rules.best_substitution(&cards), best);
+        }
+    }
+}
@@ -0,0 +1,543 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+};
+
+use num::Integer;
+
+// Node names are interned to dense ids, so that walking the map is plain indexing.
+#[derive(Debug)]
+pub struct NavigationMap {
+    // 0 for a left turn, 1 for a right turn
+    steps: Vec<usize>,
+    names: Vec<String>,
+    ids: HashMap<String, u32>,
+    // `[left, right]` neighbours of every node
+    nodes: Vec<[u32; 2]>,
+    // `partial[r][node]` is where the first `r` instructions lead from `node`
+    partial: Vec<Vec<u32>>,
+    // `jumps[k][node]` is where `2^k` passes over all instructions lead from `node`
+    jumps: Vec<Vec<u32>>,
+}
+
+impl NavigationMap {
+    pub fn from_input(input: &str) -> Self {
+        let mut lines: VecDeque<&str> = input
+            .lines()
+            .filter_map(|l| match l.trim() {
+                trimmed if !trimmed.is_empty() => Some(trimmed),
+                _ => None,
+            })
+            .collect();
+
+        let steps: Vec<usize> = lines
+            .pop_front()
+            .expect("no steps found")
+            .chars()
+            .map(|c| match c {
+                'L' => 0,
+                'R' => 1,
+                other => panic!("unknown step: {other}"),
+            })
+            .collect();
+        assert!(!steps.is_empty(), "no steps found");
+
+        let rows: Vec<(&str, &str, &str)> = lines
+            .into_iter()
+            .map(|map_row| {
+                let (key, value_tuple) = map_row.split_once('=').expect("failed to parse map row");
+                let (left, right) = value_tuple
+                    .trim()
+                    .strip_prefix('(')
+                    .and_then(|v| v.strip_suffix(')'))
+                    .and_then(|v| v.split_once(','))
+                    .expect("failed to parse tuple");
+                (key.trim(), left.trim(), right.trim())
+            })
+            .collect();
+
+        let mut names = Vec::new();
+        let mut ids = HashMap::new();
+        for (key, _, _) in rows.iter() {
+            if ids.insert(key.to_string(), names.len() as u32).is_some() {
+                panic!("duplicate node: {key}");
+            }
+            names.push(key.to_string());
+        }
+        let id = |name: &str| {
+            *ids.get(name)
+                .unwrap_or_else(|| panic!("unknown node: {name}"))
+        };
+        let nodes: Vec<[u32; 2]> = rows
+            .iter()
+            .map(|(_, left, right)| [id(left), id(right)])
+            .collect();
+
+        let mut partial = vec![(0..nodes.len() as u32).collect::<Vec<u32>>()];
+        for &step in steps.iter() {
+            let last = partial.last().expect("identity is always present");
+            let next = last.iter().map(|&n| nodes[n as usize][step]).collect();
+            partial.push(next);
+        }
+        let mut jumps = vec![partial.pop().expect("identity is always present")];
+        for _ in 1..u64::BITS {
+            let last = jumps.last().expect("a full pass is always present");
+            let next = last.iter().map(|&n| last[n as usize]).collect();
+            jumps.push(next);
+        }
+
+        Self {
+            steps,
+            names,
+            ids,
+            nodes,
+            partial,
+            jumps,
+        }
+    }
+
+    pub fn id(&self, name: &str) -> Option<u32> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, id: u32) -> &str {
+        &self.names[id as usize]
+    }
+
+    fn next(&self, current: u32, step: usize) -> u32 {
+        self.nodes[current as usize][self.steps[step % self.steps.len()]]
+    }
+
+    fn expect_id(&self, name: &str) -> u32 {
+        self.id(name)
+            .unwrap_or_else(|| panic!("unknown node: {name}"))
+    }
+
+    // number of steps needed to get from one node to another
+    pub fn walk(&self, from: &str, to: &str) -> usize {
+        let to = self.expect_id(to);
+        let mut step_count: usize = 0;
+        let mut current = self.expect_id(from);
+        while current != to {
+            current = self.next(current, step_count);
+            step_count += 1;
+        }
+        step_count
+    }
+
+    // The node reached after following `steps` instructions from `start`. Whole passes over the
+    // instructions are taken with the jump tables, the rest with a single lookup.
+    pub fn position_after(&self, start: &str, steps: u64) -> &str {
+        let len = self.steps.len() as u64;
+        let (passes, rest) = (steps / len, steps % len);
+        let mut current = self.expect_id(start);
+        for (k, jump) in self.jumps.iter().enumerate() {
+            if passes & (1 << k) != 0 {
+                current = jump[current as usize];
+            }
+        }
+        current = self.partial[rest as usize][current as usize];
+        self.name(current)
+    }
+
+    // Follows the instructions from `start` until the same node is visited at the same position in
+    // the instructions for the second time. From then on, the walk repeats forever.
+    pub fn ghost_cycle(&self, start: &str, is_end: impl Fn(&str) -> bool) -> GhostCycle {
+        let mut visited: HashMap<(u32, usize), usize> = HashMap::new();
+        let mut hits: Vec<usize> = Vec::new();
+        let mut current = self.expect_id(start);
+        let mut step_count: usize = 0;
+        let offset = loop {
+            let state = (current, step_count % self.steps.len());
+            if let Some(&first_visit) = visited.get(&state) {
+                break first_visit;
+            }
+            visited.insert(state, step_count);
+
+            if is_end(self.name(current)) {
+                hits.push(step_count);
+            }
+            current = self.next(current, step_count);
+            step_count += 1;
+        };
+
+        let (hits_before_cycle, hits_in_cycle) = hits.into_iter().partition(|&hit| hit < offset);
+        GhostCycle {
+            offset,
+            period: step_count - offset,
+            hits_before_cycle,
+            hits_in_cycle,
+        }
+    }
+
+    // number of steps after which all ghosts, starting from every node that ends with an 'A', are
+    // on nodes that end with a 'Z' at the same time; `None` if that never happens
+    pub fn ghost_walk(&self) -> Result<Option<u128>, ArrivalError> {
+        let cycles: Vec<GhostCycle> = self
+            .names
+            .iter()
+            .filter(|name| name.ends_with('A'))
+            .map(|start| self.ghost_cycle(start, |node| node.ends_with('Z')))
+            .collect();
+        first_common_arrival(&cycles)
+    }
+}
+
+// The steps at which a single ghost arrives at an end node: once at each of `hits_before_cycle`,
+// and periodically at each of `hits_in_cycle` plus any multiple of `period`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GhostCycle {
+    pub offset: usize,
+    pub period: usize,
+    pub hits_before_cycle: Vec<usize>,
+    pub hits_in_cycle: Vec<usize>,
+}
+
+impl GhostCycle {
+    pub fn arrives_at(&self, step: u128) -> bool {
+        let offset = self.offset as u128;
+        if step < offset {
+            return self.hits_before_cycle.contains(&(step as usize));
+        }
+        let in_cycle = offset + (step - offset) % self.period as u128;
+        self.hits_in_cycle.contains(&(in_cycle as usize))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArrivalError {
+    // the combined period of the ghosts does not fit into the arithmetic
+    PeriodTooLarge,
+    // the remaining ghosts were checked on too many steps without a result
+    SearchTooLong { candidates: u64 },
+}
+
+impl fmt::Display for ArrivalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArrivalError::PeriodTooLarge => {
+                write!(f, "The combined period of the ghosts is too large")
+            }
+            ArrivalError::SearchTooLong { candidates } => write!(
+                f,
+                "No common arrival among the first {candidates} candidate steps"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ArrivalError {}
+
+// Combining the periodic arrivals of the ghosts one by one can produce as many residues as the
+// product of their hit counts. Ghosts that would take the residues beyond this limit are checked
+// step by step instead, on at most `MAX_CANDIDATES` steps.
+const MAX_RESIDUES: usize = 1 << 16;
+const MAX_CANDIDATES: u64 = 1 << 26;
+
+// the first step at which all ghosts arrive at an end node simultaneously
+pub fn first_common_arrival(cycles: &[GhostCycle]) -> Result<Option<u128>, ArrivalError> {
+    // arrivals before a cycle only happen once, so they can simply be checked one by one
+    let best: Option<u128> = cycles
+        .iter()
+        .flat_map(|cycle| cycle.hits_before_cycle.iter())
+        .map(|&hit| hit as u128)
+        .filter(|&hit| cycles.iter().all(|cycle| cycle.arrives_at(hit)))
+        .min();
+
+    // From the largest offset on every ghost is within its cycle, and its arrivals are
+    // congruences. They are combined ghost by ghost using the generalized Chinese remainder
+    // theorem, keeping the distinct residues modulo the combined period. Ghosts with few hits go
+    // first to keep the residues few.
+    let Some(start) = cycles.iter().map(|cycle| cycle.offset as u128).max() else {
+        return Ok(best);
+    };
+    let mut order: Vec<&GhostCycle> = cycles.iter().collect();
+    order.sort_by_key(|cycle| cycle.hits_in_cycle.len());
+    let mut residues: Vec<u128> = vec![0];
+    let mut modulus: u128 = 1;
+    let mut merged = 0;
+    for cycle in order.iter() {
+        if residues.len() * cycle.hits_in_cycle.len() > MAX_RESIDUES {
+            break;
+        }
+        let period = cycle.period as u128;
+        let mut combined = Vec::new();
+        let mut combined_modulus = modulus;
+        for &residue in residues.iter() {
+            for &hit in cycle.hits_in_cycle.iter() {
+                if let Some((c, lcm)) = combine((residue, modulus), (hit as u128 % period, period))?
+                {
+                    combined.push(c);
+                    combined_modulus = lcm;
+                }
+            }
+        }
+        if combined.is_empty() {
+            return Ok(best);
+        }
+        combined.sort_unstable();
+        combined.dedup();
+        residues = combined;
+        modulus = combined_modulus;
+        merged += 1;
+    }
+
+    // The remaining ghosts are checked on every candidate, in increasing order, until one fits or
+    // a full period of all ghosts has passed.
+    let rest = &order[merged..];
+    let rounds = rest
+        .iter()
+        .try_fold(modulus, |lcm, cycle| {
+            let period = cycle.period as u128;
+            (lcm / lcm.gcd(&period)).checked_mul(period)
+        })
+        .map_or(u128::MAX, |lcm| lcm / modulus + 1);
+    let first_round = start / modulus;
+    let mut candidates: u64 = 0;
+    for round in first_round..first_round.saturating_add(rounds) {
+        let base = round
+            .checked_mul(modulus)
+            .ok_or(ArrivalError::PeriodTooLarge)?;
+        for &residue in residues.iter() {
+            let step = base
+                .checked_add(residue)
+                .ok_or(ArrivalError::PeriodTooLarge)?;
+            if best.is_some_and(|b| step >= b) {
+                return Ok(best);
+            }
+            if step < start {
+                continue;
+            }
+            if rest.iter().all(|cycle| cycle.arrives_at(step)) {
+                return Ok(Some(step));
+            }
+            candidates += 1;
+            if candidates >= MAX_CANDIDATES {
+                return Err(ArrivalError::SearchTooLong { candidates });
+            }
+        }
+    }
+    Ok(best)
+}
+
+// x = residue (mod modulus)
+type Congruence = (u128, u128);
+
+// The combined congruence, or `None` if the two have no common solution. Everything is computed
+// in i128, a combined period beyond that is an error.
+fn combine((a1, m1): Congruence, (a2, m2): Congruence) -> Result<Option<Congruence>, ArrivalError> {
+    let signed = |v: u128| i128::try_from(v).map_err(|_| ArrivalError::PeriodTooLarge);
+    let (a1, m1, a2, m2) = (signed(a1)?, signed(m1)?, signed(a2)?, signed(m2)?);
+    let egcd = m1.extended_gcd(&m2);
+    let g = egcd.gcd;
+    if (a2 - a1) % g != 0 {
+        return Ok(None);
+    }
+
+    // a1 + m1 * k = a2 (mod m2)  =>  k = (a2 - a1) / g * inv(m1 / g) (mod m2 / g)
+    let too_large = || ArrivalError::PeriodTooLarge;
+    let lcm = (m1 / g).checked_mul(m2).ok_or_else(too_large)?;
+    let m2_g = m2 / g;
+    let k = ((a2 - a1) / g)
+        .rem_euclid(m2_g)
+        .checked_mul(egcd.x.rem_euclid(m2_g))
+        .ok_or_else(too_large)?
+        % m2_g;
+    let x = m1
+        .checked_mul(k)
+        .and_then(|mk| mk.checked_add(a1))
+        .ok_or_else(too_large)?
+        .rem_euclid(lcm);
+    Ok(Some((x as u128, lcm as u128)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    #[test]
+    fn example_ghost_walk() {
+        let input = r#"
+            LR
+
+            11A = (11B, XXX)
+            11B = (XXX, 11Z)
+            11Z = (11B, XXX)
+            22A = (22B, XXX)
+            22B = (22C, 22C)
+            22C = (22Z, 22Z)
+            22Z = (22B, 22B)
+            XXX = (XXX, XXX)
+        "#;
+        let map = NavigationMap::from_input(input);
+        let cycle = map.ghost_cycle("22A", |node| node.ends_with('Z'));
+        assert_eq!(1, cycle.offset);
+        assert_eq!(6, cycle.period);
+        assert_eq!(vec![3, 6], cycle.hits_in_cycle);
+        assert_eq!(Ok(Some(6)), map.ghost_walk());
+    }
+
+    #[test]
+    fn offsets_that_break_lcm() {
+        // the first ghost arrives at odd steps, the second one at steps 2, 5, 8, ...
+        let input = r#"
+            L
+
+            11A = (11Z, 11Z)
+            11Z = (11C, 11C)
+            11C = (11Z, 11Z)
+            22A = (22B, 22B)
+            22B = (22Z, 22Z)
+            22Z = (22C, 22C)
+            22C = (22D, 22D)
+            22D = (22Z, 22Z)
+        "#;
+        assert_eq!(Ok(Some(5)), NavigationMap::from_input(input).ghost_walk());
+    }
+
+    #[test]
+    fn no_common_arrival() {
+        // the first ghost arrives at odd steps, the second one at even steps
+        let input = r#"
+            L
+
+            11A = (11Z, 11Z)
+            11Z = (11C, 11C)
+            11C = (11Z, 11Z)
+            22A = (22B, 22B)
+            22B = (22Z, 22Z)
+            22Z = (22C, 22C)
+            22C = (22Z, 22Z)
+        "#;
+        assert_eq!(Ok(None), NavigationMap::from_input(input).ghost_walk());
+    }
+
+    #[test]
+    fn matches_simulation() {
+        // small random graphs, compared with walking all ghosts at once
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..200 {
+            let names: Vec<String> = (0..8)
+                .map(|i| format!("{i}{i}{}", ['A', 'B', 'Z', 'A', 'C', 'Z', 'D', 'E'][i]))
+                .collect();
+            let steps: String = (0..rng.gen_range(1..5))
+                .map(|_| if rng.gen_bool(0.5) { 'L' } else { 'R' })
+                .collect();
+            let mut input = format!("{steps}\n\n");
+            for name in names.iter() {
+                let left = &names[rng.gen_range(0..names.len())];
+                let right = &names[rng.gen_range(0..names.len())];
+                input.push_str(&format!("{name} = ({left}, {right})\n"));
+            }
+            let map = NavigationMap::from_input(&input);
+
+            let mut ghosts: Vec<u32> = names
+                .iter()
+                .filter(|n| n.ends_with('A'))
+                .map(|n| map.expect_id(n))
+                .collect();
+            let mut expected = None;
+            for step in 0..10_000 {
+                if ghosts.iter().all(|&g| map.name(g).ends_with('Z')) {
+                    expected = Some(step as u128);
+                    break;
+                }
+                ghosts = ghosts.iter().map(|&g| map.next(g, step)).collect();
+            }
+            assert_eq!(Ok(expected), map.ghost_walk(), "{input}");
+        }
+    }
+
+    #[test]
+    fn many_hits_per_ghost() {
+        // every ghost arrives at two out of three steps of its cycle, so combining all hits
+        // would give millions of residues
+        let cycles: Vec<GhostCycle> = [31, 37, 41, 43, 47]
+            .into_iter()
+            .enumerate()
+            .map(|(i, period)| GhostCycle {
+                offset: i,
+                period,
+                hits_before_cycle: Vec::new(),
+                hits_in_cycle: (i..i + period).filter(|hit| hit % 3 != i % 3).collect(),
+            })
+            .collect();
+        let expected = (0..).find(|&step| cycles.iter().all(|c| c.arrives_at(step)));
+        assert_eq!(Ok(expected), first_common_arrival(&cycles));
+
+        // another ghost that arrives exactly when the first one does not
+        let mut cycles = cycles;
+        cycles.push(GhostCycle {
+            offset: 0,
+            period: 62,
+            hits_before_cycle: Vec::new(),
+            hits_in_cycle: (0..62)
+                .filter(|&hit| !cycles[0].arrives_at(hit as u128))
+                .collect(),
+        });
+        assert_eq!(Ok(None), first_common_arrival(&cycles));
+    }
+
+    #[test]
+    fn periods_beyond_i128() {
+        // consecutive periods are coprime, so the combined period is about 2^128
+        let cycles: Vec<GhostCycle> = [usize::MAX, usize::MAX - 1]
+            .into_iter()
+            .enumerate()
+            .map(|(i, period)| GhostCycle {
+                offset: 0,
+                period,
+                hits_before_cycle: Vec::new(),
+                hits_in_cycle: vec![i + 1],
+            })
+            .collect();
+        assert_eq!(
+            Err(ArrivalError::PeriodTooLarge),
+            first_common_arrival(&cycles)
+        );
+    }
+
+    #[test]
+    fn names_of_any_length() {
+        let input = r#"
+            LLR
+
+            START = (B, B)
+            B = (START, FINISH_LINE)
+            FINISH_LINE = (FINISH_LINE, FINISH_LINE)
+        "#;
+        let map = NavigationMap::from_input(input);
+        assert_eq!(6, map.walk("START", "FINISH_LINE"));
+        assert_eq!(Some(1), map.id("B"));
+        assert_eq!("FINISH_LINE", map.name(2));
+    }
+
+    #[test]
+    fn position_after_many_steps() {
+        let input = r#"
+            LRR
+
+            AAA = (BBB, CCC)
+            BBB = (CCC, AAA)
+            CCC = (DDD, BBB)
+            DDD = (AAA, DDD)
+        "#;
+        let map = NavigationMap::from_input(input);
+        let mut current = map.expect_id("AAA");
+        for step in 0..500u64 {
+            assert_eq!(map.name(current), map.position_after("AAA", step));
+            current = map.next(current, step as usize);
+        }
+
+        // far away steps are reduced to the walk's cycle
+        let cycle = map.ghost_cycle("AAA", |_| false);
+        let far: u64 = (1 << 40) + 7;
+        let reduced = cycle.offset as u64 + (far - cycle.offset as u64) % cycle.period as u64;
+        assert_eq!(
+            map.position_after("AAA", reduced),
+            map.position_after("AAA", far)
+        );
+    }
+}
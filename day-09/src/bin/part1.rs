@@ -1,47 +1,18 @@
+use day_09::{parse_histories, History};
+use num::BigInt;
+
 fn main() {
     let input = include_str!("./input.txt");
     let answer = process(input);
     println!("Part 1 answer: {answer}");
 }
 
-fn process(input: &str) -> isize {
-    let lines: Vec<Vec<isize>> = input
-        .lines()
-        .filter_map(|l| match l.trim() {
-            trimmed if !trimmed.is_empty() => {
-                let seq = trimmed
-                    .split(' ')
-                    .map(|num| num.parse().expect("failed to parse number"))
-                    .collect();
-                Some(seq)
-            }
-            _ => None,
-        })
-        .collect();
-
-    lines.iter().fold(0, |acc, line| acc + process_line(line))
-}
-
-fn process_line(line: &[isize]) -> isize {
-    let mut sequences: Vec<Vec<isize>> = vec![line.to_owned()];
-    while let Some(seq) = sequences.last_mut() {
-        if seq.iter().all(|v| *v == 0) {
-            seq.push(0);
-            break;
-        }
-
-        let next_seq: Vec<isize> = seq.windows(2).map(|w| w[1] - w[0]).collect();
-        sequences.push(next_seq);
-    }
-
-    let seq_len = sequences.len();
-    for i in 0..seq_len - 1 {
-        let prev_last = *sequences.get(seq_len - i - 1).unwrap().last().unwrap();
-        let curr = sequences.get_mut(seq_len - i - 2).unwrap();
-        let curr_new_last = curr.last().unwrap() + prev_last;
-        curr.push(curr_new_last);
-    }
-    *sequences.first().unwrap().last().unwrap()
+fn process(input: &str) -> BigInt {
+    parse_histories(input)
+        .expect("failed to parse histories")
+        .iter()
+        .map(History::next_value)
+        .sum()
 }
 
 #[cfg(test)]
@@ -50,20 +21,20 @@ mod tests {
 
     #[test]
     fn part1_process_line_1() {
-        let line = vec![0, 3, 6, 9, 12, 15];
-        assert_eq!(18, process_line(&line));
+        let history: History = "0 3 6 9 12 15".parse().unwrap();
+        assert_eq!(BigInt::from(18), history.next_value());
     }
 
     #[test]
     fn part1_process_line_2() {
-        let line = vec![1, 3, 6, 10, 15, 21];
-        assert_eq!(28, process_line(&line));
+        let history: History = "1 3 6 10 15 21".parse().unwrap();
+        assert_eq!(BigInt::from(28), history.next_value());
     }
 
     #[test]
     fn part1_process_line_3() {
-        let line = vec![10, 13, 16, 21, 30, 45];
-        assert_eq!(68, process_line(&line));
+        let history: History = "10 13 16 21 30 45".parse().unwrap();
+        assert_eq!(BigInt::from(68), history.next_value());
     }
 
     #[test]
@@ -73,6 +44,6 @@ mod tests {
             1 3 6 10 15 21
             10 13 16 21 30 45
         "#;
-        assert_eq!(114, process(input));
+        assert_eq!(BigInt::from(114), process(input));
     }
 }
@@ -0,0 +1,345 @@
+use std::{fmt, str::FromStr};
+
+use anyhow::{anyhow, Result};
+use num::{BigInt, CheckedAdd, CheckedMul, CheckedSub, FromPrimitive, Integer, Signed};
+
+// numbers the sequence engine can work with: `i64`, `i128` and `BigInt`
+pub trait SequenceNum:
+    Integer + Signed + Clone + CheckedAdd + CheckedSub + CheckedMul + FromPrimitive
+{
+}
+
+impl<N> SequenceNum for N where
+    N: Integer + Signed + Clone + CheckedAdd + CheckedSub + CheckedMul + FromPrimitive
+{
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SequenceError {
+    Empty,
+    // the last row of differences has a single non-zero value, so the degree cannot be proven
+    NoZeroRow { len: usize },
+    Overflow,
+}
+
+impl fmt::Display for SequenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SequenceError::Empty => write!(f, "Sequence has no values"),
+            SequenceError::NoZeroRow { len } => write!(
+                f,
+                "Sequence of {len} values never reaches an all-zero row of differences"
+            ),
+            SequenceError::Overflow => {
+                write!(f, "Arithmetic overflow, consider using a wider number type")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SequenceError {}
+
+// The polynomial of minimal degree through a sequence of values at indices 0, 1, 2, ..., stored in
+// the Newton forward difference form
+//   p(n) = d_0 + d_1 * C(n, 1) + d_2 * C(n, 2) + ... + d_k * C(n, k)
+// where d_j is the first value in the j-th row of differences. Since all values are integers, so
+// are the differences and the binomial coefficients, even for negative `n`, which keeps the whole
+// model exact without going through rationals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sequence<N = i64> {
+    differences: Vec<N>,
+    len: usize,
+}
+
+impl<N: SequenceNum> Sequence<N> {
+    pub fn new(values: &[N]) -> Result<Self, SequenceError> {
+        if values.is_empty() {
+            return Err(SequenceError::Empty);
+        }
+
+        let mut differences = Vec::new();
+        let mut row = values.to_vec();
+        // an all-zero row only proves the degree if it has at least one value
+        while !row.iter().all(|v| v.is_zero()) {
+            if row.len() == 1 {
+                return Err(SequenceError::NoZeroRow { len: values.len() });
+            }
+            let next = row
+                .windows(2)
+                .map(|w| w[1].checked_sub(&w[0]).ok_or(SequenceError::Overflow))
+                .collect::<Result<Vec<N>, SequenceError>>()?;
+            differences.push(row.swap_remove(0));
+            row = next;
+        }
+        if differences.is_empty() {
+            differences.push(N::zero());
+        }
+
+        Ok(Self {
+            differences,
+            len: values.len(),
+        })
+    }
+
+    // degree of the polynomial, the zero sequence has degree 0
+    pub fn degree(&self) -> usize {
+        self.differences.len() - 1
+    }
+
+    // number of values the sequence was built from
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Value at any index, including the ones before the first value. Runs in O(degree), using
+    // C(n, j + 1) = C(n, j) * (n - j) / (j + 1), where the division is always exact. Common
+    // factors are cancelled before multiplying, so that intermediate results stay small.
+    pub fn value_at(&self, index: i64) -> Result<N, SequenceError> {
+        let num = |n: i128| N::from_i128(n).ok_or(SequenceError::Overflow);
+        let mut binomial = N::one();
+        let mut value = N::zero();
+        for (j, difference) in self.differences.iter().enumerate() {
+            let term = difference
+                .checked_mul(&binomial)
+                .ok_or(SequenceError::Overflow)?;
+            value = value.checked_add(&term).ok_or(SequenceError::Overflow)?;
+            // the binomial after the last difference is never used, and may not fit
+            if j + 1 == self.differences.len() {
+                break;
+            }
+
+            let numerator = num(index as i128 - j as i128)?;
+            let denominator = num(j as i128 + 1)?;
+            let common = binomial.gcd(&denominator);
+            let numerator = numerator / (denominator / common.clone());
+            binomial = (binomial / common)
+                .checked_mul(&numerator)
+                .ok_or(SequenceError::Overflow)?;
+        }
+        Ok(value)
+    }
+
+    // the value right after the last one
+    pub fn next_value(&self) -> Result<N, SequenceError> {
+        self.value_at(self.len as i64)
+    }
+
+    // the value right before the first one
+    pub fn previous_value(&self) -> Result<N, SequenceError> {
+        self.value_at(-1)
+    }
+}
+
+impl<N> FromStr for Sequence<N>
+where
+    N: SequenceNum + FromStr,
+{
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let values = s
+            .split_whitespace()
+            .map(|num| {
+                num.parse()
+                    .map_err(|_| anyhow!("Failed to parse a number: {num}"))
+            })
+            .collect::<Result<Vec<N>>>()?;
+        Ok(Sequence::new(&values)?)
+    }
+}
+
+// one sequence per non-empty line
+pub fn parse_sequences<N>(input: &str) -> Result<Vec<Sequence<N>>>
+where
+    N: SequenceNum + FromStr,
+{
+    input
+        .lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty())
+        .map(|l| l.parse())
+        .collect()
+}
+
+// A sensor history that is modelled with `i64` as long as that is wide enough and is promoted to
+// `BigInt` as soon as any computation overflows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum History {
+    Fixed(Sequence<i64>),
+    Big(Sequence<BigInt>),
+}
+
+impl History {
+    pub fn new(values: &[BigInt]) -> Result<Self, SequenceError> {
+        let fixed: Option<Vec<i64>> = values.iter().map(|v| i64::try_from(v).ok()).collect();
+        match fixed.map(|values| Sequence::new(&values)) {
+            Some(Ok(sequence)) => Ok(History::Fixed(sequence)),
+            Some(Err(SequenceError::Overflow)) | None => Ok(History::Big(Sequence::new(values)?)),
+            Some(Err(error)) => Err(error),
+        }
+    }
+
+    pub fn degree(&self) -> usize {
+        match self {
+            History::Fixed(sequence) => sequence.degree(),
+            History::Big(sequence) => sequence.degree(),
+        }
+    }
+
+    pub fn is_big(&self) -> bool {
+        matches!(self, History::Big(_))
+    }
+
+    pub fn value_at(&self, index: i64) -> BigInt {
+        match self {
+            History::Fixed(sequence) => match sequence.value_at(index) {
+                Ok(value) => BigInt::from(value),
+                Err(_) => History::promote(sequence)
+                    .value_at(index)
+                    .expect("big integers do not overflow"),
+            },
+            History::Big(sequence) => sequence
+                .value_at(index)
+                .expect("big integers do not overflow"),
+        }
+    }
+
+    pub fn next_value(&self) -> BigInt {
+        let len = match self {
+            History::Fixed(sequence) => sequence.len(),
+            History::Big(sequence) => sequence.len(),
+        };
+        self.value_at(len as i64)
+    }
+
+    pub fn previous_value(&self) -> BigInt {
+        self.value_at(-1)
+    }
+
+    fn promote(sequence: &Sequence<i64>) -> Sequence<BigInt> {
+        Sequence {
+            differences: sequence
+                .differences
+                .iter()
+                .map(|&d| BigInt::from(d))
+                .collect(),
+            len: sequence.len,
+        }
+    }
+}
+
+impl FromStr for History {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let values = s
+            .split_whitespace()
+            .map(|num| {
+                num.parse()
+                    .map_err(|_| anyhow!("Failed to parse a number: {num}"))
+            })
+            .collect::<Result<Vec<BigInt>>>()?;
+        Ok(History::new(&values)?)
+    }
+}
+
+// one history per non-empty line
+pub fn parse_histories(input: &str) -> Result<Vec<History>> {
+    input
+        .lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty())
+        .map(|l| l.parse())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extrapolation() {
+        let sequence: Sequence = "10 13 16 21 30 45".parse().unwrap();
+        assert_eq!(3, sequence.degree());
+        assert_eq!(68, sequence.next_value().unwrap());
+        assert_eq!(5, sequence.previous_value().unwrap());
+        assert_eq!(10, sequence.value_at(0).unwrap());
+        assert_eq!(45, sequence.value_at(5).unwrap());
+
+        // n^2 is recovered from three values and evaluated anywhere
+        let squares = Sequence::new(&[0, 1, 4, 9]).unwrap();
+        assert_eq!(2, squares.degree());
+        assert_eq!(1_000_000, squares.value_at(1000).unwrap());
+        assert_eq!(49, squares.value_at(-7).unwrap());
+
+        let zeros = Sequence::new(&[0, 0, 0]).unwrap();
+        assert_eq!(0, zeros.degree());
+        assert_eq!(0, zeros.value_at(-100).unwrap());
+    }
+
+    #[test]
+    fn matches_difference_tables() {
+        // cubic 2n^3 - 5n^2 + n - 7 sampled at a few points
+        let p = |n: i64| 2 * n.pow(3) - 5 * n.pow(2) + n - 7;
+        let values: Vec<i64> = (0..8).map(p).collect();
+        let sequence = Sequence::new(&values).unwrap();
+        assert_eq!(3, sequence.degree());
+        for n in -50..50 {
+            assert_eq!(p(n), sequence.value_at(n).unwrap());
+        }
+    }
+
+    #[test]
+    fn never_reaches_zero_row() {
+        assert_eq!(
+            Err(SequenceError::NoZeroRow { len: 5 }),
+            Sequence::new(&[1, 2, 4, 8, 16])
+        );
+        assert!(Sequence::new(&[5]).is_err());
+        assert_eq!(Err(SequenceError::Empty), Sequence::<i64>::new(&[]));
+        assert!(Sequence::new(&[5, 5]).is_ok());
+        assert!(parse_sequences::<i64>("1 2 3\n1 x 3").is_err());
+    }
+
+    #[test]
+    fn overflow_is_reported() {
+        let values = [i64::MIN, i64::MAX];
+        assert_eq!(Err(SequenceError::Overflow), Sequence::new(&values));
+
+        let step = i64::MAX as i128 - i64::MIN as i128;
+        let wide: Vec<i128> = (0..3).map(|n| i64::MIN as i128 + n * step).collect();
+        assert_eq!(1, Sequence::new(&wide).unwrap().degree());
+
+        // n^4 fits into i64 at the given points, but not at the extrapolated one
+        let quartic: Sequence = "0 1 16 81 256 625".parse().unwrap();
+        assert_eq!(Err(SequenceError::Overflow), quartic.value_at(1 << 20));
+        let quartic: Sequence<i128> = "0 1 16 81 256 625".parse().unwrap();
+        assert_eq!(Ok(1 << 80), quartic.value_at(1 << 20));
+
+        // only the binomials that are actually used have to fit
+        let linear = Sequence::<i64>::new(&[0, 1, 2]).unwrap();
+        assert_eq!(Ok(1 << 40), linear.value_at(1 << 40));
+        assert_eq!(Ok(i64::MAX), linear.value_at(i64::MAX));
+        assert_eq!(Ok(-1), linear.previous_value());
+    }
+
+    #[test]
+    fn automatic_promotion() {
+        let history: History = "0 1 16 81 256 625".parse().unwrap();
+        assert!(!history.is_big());
+        assert_eq!(BigInt::from(1) << 80, history.value_at(1 << 20));
+        assert_eq!(BigInt::from(1296), history.next_value());
+
+        // the differences of a long history of n^30 are far beyond i64
+        let values: Vec<BigInt> = (0..100).map(|n| BigInt::from(n).pow(30)).collect();
+        let history = History::new(&values).unwrap();
+        assert!(history.is_big());
+        assert_eq!(30, history.degree());
+        assert_eq!(BigInt::from(100).pow(30), history.next_value());
+        assert_eq!(BigInt::from(1), history.previous_value());
+    }
+}
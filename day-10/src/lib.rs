@@ -0,0 +1,574 @@
+use std::collections::HashSet;
+
+use anyhow::{bail, Result};
+
+// (row, column)
+pub type Pos = (usize, usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    pub fn opposite(&self) -> Self {
+        match self {
+            Direction::North => Direction::South,
+            Direction::East => Direction::West,
+            Direction::South => Direction::North,
+            Direction::West => Direction::East,
+        }
+    }
+
+    fn offset(&self) -> (isize, isize) {
+        match self {
+            Direction::North => (-1, 0),
+            Direction::East => (0, 1),
+            Direction::South => (1, 0),
+            Direction::West => (0, -1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PipeType {
+    NorthSouth,
+    EastWest,
+    NorthEast,
+    NorthWest,
+    SouthWest,
+    SouthEast,
+}
+
+impl PipeType {
+    pub const ALL: [PipeType; 6] = [
+        PipeType::NorthSouth,
+        PipeType::EastWest,
+        PipeType::NorthEast,
+        PipeType::NorthWest,
+        PipeType::SouthWest,
+        PipeType::SouthEast,
+    ];
+
+    // Two pipes are connected if each of them has an opening towards the other one, so the
+    // openings are all that is needed to decide which pipes can follow each other.
+    pub fn connections(&self) -> [Direction; 2] {
+        match self {
+            PipeType::NorthSouth => [Direction::North, Direction::South],
+            PipeType::EastWest => [Direction::East, Direction::West],
+            PipeType::NorthEast => [Direction::North, Direction::East],
+            PipeType::NorthWest => [Direction::North, Direction::West],
+            PipeType::SouthWest => [Direction::South, Direction::West],
+            PipeType::SouthEast => [Direction::South, Direction::East],
+        }
+    }
+
+    pub fn connects(&self, direction: Direction) -> bool {
+        self.connections().contains(&direction)
+    }
+
+    pub fn from_connections(a: Direction, b: Direction) -> Option<Self> {
+        PipeType::ALL
+            .into_iter()
+            .find(|p| a != b && p.connects(a) && p.connects(b))
+    }
+}
+
+impl TryFrom<char> for PipeType {
+    type Error = anyhow::Error;
+
+    fn try_from(value: char) -> Result<Self> {
+        Ok(match value {
+            '|' => PipeType::NorthSouth,
+            '-' => PipeType::EastWest,
+            'L' => PipeType::NorthEast,
+            'J' => PipeType::NorthWest,
+            '7' => PipeType::SouthWest,
+            'F' => PipeType::SouthEast,
+            _ => bail!("Unrecognized pipe: {value}"),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Symbol {
+    Start,
+    Ground,
+    Pipe(PipeType),
+}
+
+impl TryFrom<char> for Symbol {
+    type Error = anyhow::Error;
+
+    fn try_from(value: char) -> Result<Self> {
+        Ok(match value {
+            '.' => Symbol::Ground,
+            'S' => Symbol::Start,
+            s => Symbol::Pipe(s.try_into()?),
+        })
+    }
+}
+
+// The direction of travel along a loop, as seen on the map with rows growing downwards. Loops are
+// walked by leaving their first tile through its first opening in the order north, east, south,
+// west.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    Clockwise,
+    CounterClockwise,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefectKind {
+    // the opening leads off the map or onto the ground
+    Dangling,
+    // the opening leads onto a pipe that does not connect back
+    Inconsistent,
+}
+
+// a pipe opening that does not connect to anything
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Defect {
+    pub pos: Pos,
+    pub direction: Direction,
+    pub kind: DefectKind,
+}
+
+// A closed loop of pipes, with tiles in walking order. A loop through the start tile begins there,
+// any other loop begins at its first tile in reading order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Loop {
+    pub tiles: Vec<Pos>,
+}
+
+impl Loop {
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    pub fn contains(&self, pos: Pos) -> bool {
+        self.tiles.contains(&pos)
+    }
+
+    // The tile farthest away from the first one, together with its distance along the loop. A
+    // loop on a grid always has even length, so there is exactly one such tile.
+    pub fn farthest(&self) -> (usize, Pos) {
+        let distance = self.len() / 2;
+        (distance, self.tiles[distance])
+    }
+
+    // Twice the signed area of the polygon through the tile centres (shoelace formula), negative
+    // for loops that run counter-clockwise on the map.
+    fn double_signed_area(&self) -> i64 {
+        let n = self.tiles.len();
+        (0..n)
+            .map(|i| {
+                let (r1, c1) = self.tiles[i];
+                let (r2, c2) = self.tiles[(i + 1) % n];
+                c1 as i64 * r2 as i64 - c2 as i64 * r1 as i64
+            })
+            .sum()
+    }
+
+    // area of the polygon through the tile centres
+    pub fn area(&self) -> usize {
+        self.double_signed_area().unsigned_abs() as usize / 2
+    }
+
+    // Number of tiles enclosed by the loop, in O(loop length). The loop passes through the centres
+    // of its tiles, which are lattice points, so Pick's theorem A = i + b / 2 - 1 gives the number
+    // of interior lattice points from the area and the number of boundary points.
+    pub fn enclosed_count(&self) -> usize {
+        (self.double_signed_area().unsigned_abs() as usize + 2 - self.len()) / 2
+    }
+
+    pub fn orientation(&self) -> Orientation {
+        if self.double_signed_area() > 0 {
+            Orientation::Clockwise
+        } else {
+            Orientation::CounterClockwise
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PipeMap {
+    map: Vec<Vec<Symbol>>,
+    start: Option<Pos>,
+    // the pipe hidden under the start tile, if it could be inferred
+    start_pipe: Option<PipeType>,
+}
+
+impl PipeMap {
+    pub fn from_input(input: &str) -> Result<Self> {
+        let map = input
+            .lines()
+            .map(|l| l.trim())
+            .filter(|l| !l.is_empty())
+            .map(|l| {
+                l.chars()
+                    .map(Symbol::try_from)
+                    .collect::<Result<Vec<Symbol>>>()
+            })
+            .collect::<Result<Vec<Vec<Symbol>>>>()?;
+        if map.iter().any(|row| row.len() != map[0].len()) {
+            bail!("Map rows have different lengths");
+        }
+
+        let starts: Vec<Pos> = map
+            .iter()
+            .enumerate()
+            .flat_map(|(r, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter(|(_, &s)| s == Symbol::Start)
+                    .map(move |(c, _)| (r, c))
+            })
+            .collect();
+        if starts.len() > 1 {
+            bail!("Can't have more than one start tile in the same map");
+        }
+
+        let mut pipe_map = Self {
+            map,
+            start: starts.first().copied(),
+            start_pipe: None,
+        };
+        pipe_map.start_pipe = pipe_map.infer_start_pipe();
+        Ok(pipe_map)
+    }
+
+    pub fn start(&self) -> Option<Pos> {
+        self.start
+    }
+
+    pub fn start_pipe(&self) -> Option<PipeType> {
+        self.start_pipe
+    }
+
+    // The start tile is a pipe connected to two of its neighbours. If more than two neighbours
+    // connect to it, the pair that closes a loop wins.
+    fn infer_start_pipe(&mut self) -> Option<PipeType> {
+        let start = self.start?;
+        let open: Vec<Direction> = Direction::ALL
+            .into_iter()
+            .filter(|&d| {
+                self.neighbour(start, d)
+                    .and_then(|n| self.pipe(n))
+                    .is_some_and(|p| p.connects(d.opposite()))
+            })
+            .collect();
+
+        let candidates: Vec<PipeType> = open
+            .iter()
+            .enumerate()
+            .flat_map(|(i, &a)| open[i + 1..].iter().map(move |&b| (a, b)))
+            .filter_map(|(a, b)| PipeType::from_connections(a, b))
+            .collect();
+        for &candidate in candidates.iter() {
+            self.start_pipe = Some(candidate);
+            if self.walk_loop(start).is_some() {
+                return Some(candidate);
+            }
+        }
+        candidates.first().copied()
+    }
+
+    pub fn rows(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn columns(&self) -> usize {
+        self.map.first().map_or(0, |row| row.len())
+    }
+
+    pub fn symbol(&self, pos: Pos) -> Symbol {
+        self.map[pos.0][pos.1]
+    }
+
+    // the pipe at a given tile, including the one inferred for the start tile
+    pub fn pipe(&self, pos: Pos) -> Option<PipeType> {
+        match self.symbol(pos) {
+            Symbol::Pipe(p) => Some(p),
+            Symbol::Start => self.start_pipe,
+            Symbol::Ground => None,
+        }
+    }
+
+    pub fn neighbour(&self, pos: Pos, direction: Direction) -> Option<Pos> {
+        let (dr, dc) = direction.offset();
+        let r = pos.0.checked_add_signed(dr)?;
+        let c = pos.1.checked_add_signed(dc)?;
+        (r < self.rows() && c < self.columns()).then_some((r, c))
+    }
+
+    // the tile reached through an opening of the pipe at `pos`, if the two pipes are connected
+    fn follow(&self, pos: Pos, direction: Direction) -> Result<Pos, DefectKind> {
+        let next = self.neighbour(pos, direction).ok_or(DefectKind::Dangling)?;
+        match self.pipe(next) {
+            None => Err(DefectKind::Dangling),
+            Some(p) if p.connects(direction.opposite()) => Ok(next),
+            Some(_) => Err(DefectKind::Inconsistent),
+        }
+    }
+
+    // Walks along the pipes from `from` until it gets back, or returns `None` if the pipes break
+    // off on the way.
+    fn walk_loop(&self, from: Pos) -> Option<Loop> {
+        match self.trace(from, &HashSet::new()) {
+            (tiles, true) => Some(Loop { tiles }),
+            (_, false) => None,
+        }
+    }
+
+    // All tiles walked from `from`, and whether the walk got back to it. A walk reaching one of the
+    // `finished` tiles counts as broken: a pipe connected to a loop is part of that loop, so the
+    // tiles that are finished are either on another loop or known not to be on any.
+    fn trace(&self, from: Pos, finished: &HashSet<Pos>) -> (Vec<Pos>, bool) {
+        let mut tiles = vec![from];
+        let Some(pipe) = self.pipe(from) else {
+            return (tiles, false);
+        };
+        let mut direction = pipe.connections()[0];
+        let mut current = from;
+        loop {
+            let Ok(next) = self.follow(current, direction) else {
+                return (tiles, false);
+            };
+            if finished.contains(&next) {
+                return (tiles, false);
+            }
+            if next == from {
+                return (tiles, true);
+            }
+            current = next;
+            tiles.push(current);
+            let [a, b] = self
+                .pipe(current)
+                .expect("connected tiles are pipes")
+                .connections();
+            direction = if a == direction.opposite() { b } else { a };
+        }
+    }
+
+    // the loop through the start tile
+    pub fn start_loop(&self) -> Option<Loop> {
+        self.walk_loop(self.start?)
+    }
+
+    // every closed loop on the map, ordered by their topmost, leftmost tiles
+    pub fn loops(&self) -> Vec<Loop> {
+        let mut visited: HashSet<Pos> = HashSet::new();
+        let mut loops = Vec::new();
+        if let Some(start_loop) = self.start_loop() {
+            visited.extend(start_loop.tiles.iter().copied());
+            loops.push(start_loop);
+        }
+        for r in 0..self.rows() {
+            for c in 0..self.columns() {
+                if visited.contains(&(r, c)) {
+                    continue;
+                }
+                // tiles on a broken walk lead to the same break, so none of them is on a loop
+                let (tiles, closed) = self.trace((r, c), &visited);
+                visited.extend(tiles.iter().copied());
+                if closed {
+                    loops.push(Loop { tiles });
+                }
+            }
+        }
+        loops.sort_by_key(|l| l.tiles.iter().min().copied());
+        loops
+    }
+
+    // The tiles enclosed by a loop, found by scanning every row from left to right. Crossing a loop
+    // tile with an opening to the north toggles between outside and inside, which also handles
+    // runs along the loop: `L-7` crosses it once, `L-J` does not cross it at all.
+    pub fn enclosed_tiles(&self, pipe_loop: &Loop) -> HashSet<Pos> {
+        let on_loop: HashSet<Pos> = pipe_loop.tiles.iter().copied().collect();
+        let mut enclosed = HashSet::new();
+        for r in 0..self.rows() {
+            let mut inside = false;
+            for c in 0..self.columns() {
+                if !on_loop.contains(&(r, c)) {
+                    if inside {
+                        enclosed.insert((r, c));
+                    }
+                } else if self
+                    .pipe((r, c))
+                    .is_some_and(|p| p.connects(Direction::North))
+                {
+                    inside = !inside;
+                }
+            }
+        }
+        enclosed
+    }
+
+    // every pipe opening that does not lead to a pipe connecting back
+    pub fn defects(&self) -> Vec<Defect> {
+        let mut defects = Vec::new();
+        for r in 0..self.rows() {
+            for c in 0..self.columns() {
+                let Some(pipe) = self.pipe((r, c)) else {
+                    continue;
+                };
+                for direction in pipe.connections() {
+                    if let Err(kind) = self.follow((r, c), direction) {
+                        defects.push(Defect {
+                            pos: (r, c),
+                            direction,
+                            kind,
+                        });
+                    }
+                }
+            }
+        }
+        defects
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn start_loop() {
+        let input = r#"
+            7-F7-
+            .FJ|7
+            SJLL7
+            |F--J
+            LJ.LJ
+        "#;
+        let pipe_map = PipeMap::from_input(input).unwrap();
+        assert_eq!(Some((2, 0)), pipe_map.start());
+        assert_eq!(Some(PipeType::SouthEast), pipe_map.start_pipe());
+
+        let start_loop = pipe_map.start_loop().unwrap();
+        assert_eq!(16, start_loop.len());
+        assert_eq!((8, (2, 4)), start_loop.farthest());
+        assert_eq!(Orientation::CounterClockwise, start_loop.orientation());
+        // Pick's theorem: area = inside + boundary / 2 - 1
+        assert_eq!(1 + 16 / 2 - 1, start_loop.area());
+    }
+
+    #[test]
+    fn every_loop() {
+        let input = r#"
+            F7.S7
+            LJ.||
+            F-7LJ
+            L7|..
+            .LJ.-
+        "#;
+        let pipe_map = PipeMap::from_input(input).unwrap();
+        assert_eq!(Some(PipeType::SouthEast), pipe_map.start_pipe());
+        let loops = pipe_map.loops();
+        assert_eq!(3, loops.len());
+        assert_eq!(
+            vec![4, 6, 8],
+            loops.iter().map(|l| l.len()).collect::<Vec<_>>()
+        );
+        assert_eq!(vec![(0, 0), (1, 0), (1, 1), (0, 1)], loops[0].tiles);
+        assert_eq!(Orientation::CounterClockwise, loops[0].orientation());
+        assert_eq!(1, loops[0].area());
+        assert!(loops[1].contains((0, 3)));
+        assert_eq!(Orientation::CounterClockwise, loops[2].orientation());
+        assert_eq!(3, loops[2].area());
+    }
+
+    #[test]
+    fn defects() {
+        let input = r#"
+            .F7
+            -|L
+        "#;
+        let pipe_map = PipeMap::from_input(input).unwrap();
+        assert_eq!(None, pipe_map.start());
+        assert!(pipe_map.loops().is_empty());
+        let defect = |pos, direction, kind| Defect {
+            pos,
+            direction,
+            kind,
+        };
+        assert_eq!(
+            vec![
+                defect((1, 0), Direction::East, DefectKind::Inconsistent),
+                defect((1, 0), Direction::West, DefectKind::Dangling),
+                defect((1, 1), Direction::South, DefectKind::Dangling),
+                defect((1, 2), Direction::East, DefectKind::Dangling),
+            ],
+            pipe_map.defects()
+        );
+        assert!(PipeMap::from_input("S.S").is_err());
+        assert!(PipeMap::from_input("F7\nLX").is_err());
+    }
+
+    #[test]
+    fn long_broken_pipes() {
+        // every column is a single broken pipe, with a loop of four tiles underneath
+        let mut input = "|".repeat(500) + "\n";
+        input = input.repeat(500);
+        input.push_str(&format!("F7{}\nLJ{}\n", ".".repeat(498), ".".repeat(498)));
+        let pipe_map = PipeMap::from_input(&input).unwrap();
+        let loops = pipe_map.loops();
+        assert_eq!(1, loops.len());
+        assert_eq!(vec![(500, 0), (501, 0), (501, 1), (500, 1)], loops[0].tiles);
+    }
+
+    #[test]
+    fn enclosed_tiles() {
+        let input = r#"
+            ..........
+            .S------7.
+            .|F----7|.
+            .||....||.
+            .||....||.
+            .|L-7F-J|.
+            .|..||..|.
+            .L--JL--J.
+            ..........
+        "#;
+        let pipe_map = PipeMap::from_input(input).unwrap();
+        let start_loop = pipe_map.start_loop().unwrap();
+        assert_eq!(4, start_loop.enclosed_count());
+        let enclosed = pipe_map.enclosed_tiles(&start_loop);
+        assert_eq!(HashSet::from([(6, 2), (6, 3), (6, 6), (6, 7)]), enclosed);
+
+        let input = r#"
+            FF7FSF7F7F7F7F7F---7
+            L|LJ||||||||||||F--J
+            FL-7LJLJ||||||LJL-77
+            F--JF--7||LJLJ7F7FJ-
+            L---JF-JLJ.||-FJLJJ7
+            |F|F-JF---7F7-L7L|7|
+            |FFJF7L7F-JF7|JL---7
+            7-L-JL7||F7|L7F-7F7|
+            L.L7LFJ|||||FJL7||LJ
+            L7JLJL-JLJLJL--JLJ.L
+        "#;
+        let pipe_map = PipeMap::from_input(input).unwrap();
+        for pipe_loop in pipe_map.loops() {
+            assert_eq!(
+                pipe_loop.enclosed_count(),
+                pipe_map.enclosed_tiles(&pipe_loop).len()
+            );
+        }
+        assert_eq!(10, pipe_map.start_loop().unwrap().enclosed_count());
+    }
+}
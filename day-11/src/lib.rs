@@ -0,0 +1,364 @@
+use std::cell::OnceCell;
+
+use anyhow::{bail, Result};
+
+// (row, column)
+pub type Pos = (usize, usize);
+
+// A field of galaxies where every empty row and column is wider than it looks. Galaxies are named
+// by numbers starting from 1, in reading order of the original image.
+#[derive(Debug, Clone)]
+pub struct GalaxyField {
+    galaxies: Vec<Pos>,
+    row_widths: Vec<u64>,
+    column_widths: Vec<u64>,
+    empty_rows: Vec<bool>,
+    empty_columns: Vec<bool>,
+    // expanded height and width, which together have to fit into u64, so that neither the
+    // coordinates nor the distances between galaxies can overflow
+    size: (u64, u64),
+    // expanded coordinates of the galaxies, computed from the widths when first needed
+    coordinates: OnceCell<Vec<(u64, u64)>>,
+}
+
+impl GalaxyField {
+    pub fn from_input(input: &str) -> Result<Self> {
+        let lines: Vec<&str> = input
+            .lines()
+            .map(|l| l.trim())
+            .filter(|l| !l.is_empty())
+            .collect();
+        let columns = lines.first().map_or(0, |l| l.len());
+
+        let mut galaxies = Vec::new();
+        for (row, line) in lines.iter().enumerate() {
+            if line.len() != columns {
+                bail!("Image rows have different lengths");
+            }
+            for (column, c) in line.chars().enumerate() {
+                match c {
+                    '#' => galaxies.push((row, column)),
+                    '.' => (),
+                    other => bail!("Unknown symbol in the image: {other}"),
+                }
+            }
+        }
+        Self::from_galaxies(lines.len(), columns, galaxies)
+    }
+
+    // a field of the given size, with galaxies at the given positions of the original image
+    pub fn from_galaxies(rows: usize, columns: usize, mut galaxies: Vec<Pos>) -> Result<Self> {
+        let mut empty_rows = vec![true; rows];
+        let mut empty_columns = vec![true; columns];
+        for &(row, column) in galaxies.iter() {
+            if row >= rows || column >= columns {
+                bail!("Galaxy at ({row}, {column}) is outside of the image");
+            }
+            empty_rows[row] = false;
+            empty_columns[column] = false;
+        }
+        galaxies.sort();
+
+        Ok(Self {
+            galaxies,
+            row_widths: vec![1; rows],
+            column_widths: vec![1; columns],
+            empty_rows,
+            empty_columns,
+            size: (rows as u64, columns as u64),
+            coordinates: OnceCell::new(),
+        })
+    }
+
+    // every empty line becomes `factor` lines wide
+    pub fn with_expansion(self, factor: u64) -> Result<Self> {
+        self.with_row_expansion(factor)?
+            .with_column_expansion(factor)
+    }
+
+    pub fn with_row_expansion(mut self, factor: u64) -> Result<Self> {
+        let empty = self.empty_rows.iter().filter(|&&empty| empty).count() as u64;
+        let height = empty
+            .checked_mul(factor)
+            .and_then(|h| h.checked_add(self.empty_rows.len() as u64 - empty));
+        self.size = fits(height, Some(self.size.1))?;
+        for (width, _) in self
+            .row_widths
+            .iter_mut()
+            .zip(self.empty_rows.iter())
+            .filter(|(_, &empty)| empty)
+        {
+            *width = factor;
+        }
+        self.coordinates.take();
+        Ok(self)
+    }
+
+    pub fn with_column_expansion(mut self, factor: u64) -> Result<Self> {
+        let empty = self.empty_columns.iter().filter(|&&empty| empty).count() as u64;
+        let width = empty
+            .checked_mul(factor)
+            .and_then(|w| w.checked_add(self.empty_columns.len() as u64 - empty));
+        self.size = fits(Some(self.size.0), width)?;
+        for (width, _) in self
+            .column_widths
+            .iter_mut()
+            .zip(self.empty_columns.iter())
+            .filter(|(_, &empty)| empty)
+        {
+            *width = factor;
+        }
+        self.coordinates.take();
+        Ok(self)
+    }
+
+    // a single empty row expands by its own factor, rows with galaxies never expand
+    pub fn with_row_factor(mut self, row: usize, factor: u64) -> Result<Self> {
+        let Some(&empty) = self.empty_rows.get(row) else {
+            bail!("Row {row} is outside of the image");
+        };
+        if empty {
+            let height = (self.size.0 - self.row_widths[row]).checked_add(factor);
+            self.size = fits(height, Some(self.size.1))?;
+            self.row_widths[row] = factor;
+            self.coordinates.take();
+        }
+        Ok(self)
+    }
+
+    pub fn with_column_factor(mut self, column: usize, factor: u64) -> Result<Self> {
+        let Some(&empty) = self.empty_columns.get(column) else {
+            bail!("Column {column} is outside of the image");
+        };
+        if empty {
+            let width = (self.size.1 - self.column_widths[column]).checked_add(factor);
+            self.size = fits(Some(self.size.0), width)?;
+            self.column_widths[column] = factor;
+            self.coordinates.take();
+        }
+        Ok(self)
+    }
+
+    fn coordinates(&self) -> &[(u64, u64)] {
+        self.coordinates.get_or_init(|| {
+            let offsets = |widths: &[u64]| -> Vec<u64> {
+                let mut offsets = Vec::with_capacity(widths.len());
+                let mut offset = 0;
+                for width in widths {
+                    offsets.push(offset);
+                    offset += width;
+                }
+                offsets
+            };
+            let row_offsets = offsets(&self.row_widths);
+            let column_offsets = offsets(&self.column_widths);
+            self.galaxies
+                .iter()
+                .map(|&(row, column)| (row_offsets[row], column_offsets[column]))
+                .collect()
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.galaxies.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.galaxies.is_empty()
+    }
+
+    // expanded coordinates of a galaxy
+    pub fn galaxy(&self, name: usize) -> Option<(u64, u64)> {
+        self.coordinates().get(name.checked_sub(1)?).copied()
+    }
+
+    pub fn distance(&self, a: usize, b: usize) -> Option<u64> {
+        let (a_row, a_column) = self.galaxy(a)?;
+        let (b_row, b_column) = self.galaxy(b)?;
+        Some(a_row.abs_diff(b_row) + a_column.abs_diff(b_column))
+    }
+
+    // the closest other galaxy and its distance, ties go to the lower name
+    pub fn nearest(&self, name: usize) -> Option<(usize, u64)> {
+        self.others(name)?.min_by_key(|&(other, d)| (d, other))
+    }
+
+    // the most distant other galaxy and its distance, ties go to the lower name
+    pub fn farthest(&self, name: usize) -> Option<(usize, u64)> {
+        self.others(name)?
+            .max_by_key(|&(other, d)| (d, std::cmp::Reverse(other)))
+    }
+
+    fn others(&self, name: usize) -> Option<impl Iterator<Item = (usize, u64)> + '_> {
+        self.galaxy(name)?;
+        Some(
+            (1..=self.len())
+                .filter(move |&other| other != name)
+                .map(move |other| {
+                    let d = self.distance(name, other).expect("both galaxies exist");
+                    (other, d)
+                }),
+        )
+    }
+
+    // Sum of distances over all pairs of galaxies. Manhattan distances split into the two axes, and
+    // along one axis the i-th smallest coordinate x_i contributes i * x_i minus the sum of all
+    // smaller coordinates, which is a prefix sum.
+    pub fn sum_of_distances(&self) -> u128 {
+        let axis_sum = |mut values: Vec<u64>| -> u128 {
+            values.sort_unstable();
+            let mut prefix: u128 = 0;
+            let mut total: u128 = 0;
+            for (i, &value) in values.iter().enumerate() {
+                total += i as u128 * value as u128 - prefix;
+                prefix += value as u128;
+            }
+            total
+        };
+        let coordinates = self.coordinates();
+        axis_sum(coordinates.iter().map(|c| c.0).collect())
+            + axis_sum(coordinates.iter().map(|c| c.1).collect())
+    }
+}
+
+fn fits(height: Option<u64>, width: Option<u64>) -> Result<(u64, u64)> {
+    match (height, width) {
+        (Some(height), Some(width)) if height.checked_add(width).is_some() => Ok((height, width)),
+        _ => bail!("The expanded image is too large for u64 coordinates"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    const EXAMPLE: &str = r#"
+        ...#......
+        .......#..
+        #.........
+        ..........
+        ......#...
+        .#........
+        .........#
+        ..........
+        .......#..
+        #...#.....
+    "#;
+
+    #[test]
+    fn named_distances() {
+        let field = GalaxyField::from_input(EXAMPLE)
+            .unwrap()
+            .with_expansion(2)
+            .unwrap();
+        assert_eq!(9, field.len());
+        assert_eq!(Some(9), field.distance(5, 9));
+        assert_eq!(Some(15), field.distance(1, 7));
+        assert_eq!(Some(17), field.distance(3, 6));
+        assert_eq!(Some(5), field.distance(8, 9));
+        assert_eq!(None, field.distance(0, 1));
+        assert_eq!(None, field.distance(1, 10));
+
+        assert_eq!(Some((2, 6)), field.nearest(1));
+        assert_eq!(Some((9, 5)), field.nearest(8));
+        assert_eq!(Some((6, 15)), field.farthest(1));
+        assert_eq!(Some(15), field.distance(1, 6));
+    }
+
+    #[test]
+    fn expansion_factors() {
+        let field = GalaxyField::from_input(EXAMPLE).unwrap();
+        assert_eq!(292, field.sum_of_distances());
+        assert_eq!(
+            374,
+            field.clone().with_expansion(2).unwrap().sum_of_distances()
+        );
+        assert_eq!(
+            1030,
+            field.clone().with_expansion(10).unwrap().sum_of_distances()
+        );
+        assert_eq!(
+            8410,
+            field
+                .clone()
+                .with_expansion(100)
+                .unwrap()
+                .sum_of_distances()
+        );
+
+        // empty rows are 3 and 7, empty columns are 2, 5 and 8
+        let field = field
+            .with_row_expansion(10)
+            .and_then(|field| field.with_column_expansion(1))
+            .unwrap();
+        assert_eq!(Some((9 + 2 * 9, 4)), field.galaxy(9));
+        let field = field
+            .with_row_factor(7, 1)
+            .and_then(|field| field.with_column_factor(2, 4))
+            .unwrap();
+        assert_eq!(Some((9 + 9, 4 + 3)), field.galaxy(9));
+        // lines with galaxies keep their width
+        let field = field.with_row_factor(0, 50).unwrap();
+        assert_eq!(Some((0, 3 + 3)), field.galaxy(1));
+        assert!(field.clone().with_row_factor(10, 2).is_err());
+        assert!(field.with_column_factor(10, 2).is_err());
+    }
+
+    #[test]
+    fn a_factor_for_every_line() {
+        // the coordinates are only computed once all factors are set
+        let size = 100_000;
+        let mut field =
+            GalaxyField::from_galaxies(size, size, vec![(0, 0), (size - 1, size - 1)]).unwrap();
+        for line in 1..size - 1 {
+            field = field
+                .with_row_factor(line, line as u64)
+                .and_then(|field| field.with_column_factor(line, 1))
+                .unwrap();
+        }
+        let rows: u64 = (1..size as u64 - 1).sum();
+        assert_eq!(Some(rows + 1 + size as u64 - 1), field.distance(1, 2));
+    }
+
+    #[test]
+    fn matches_pairwise_sum() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..20 {
+            let galaxies: Vec<Pos> = (0..rng.gen_range(1..=40))
+                .map(|_| (rng.gen_range(0..30), rng.gen_range(0..30)))
+                .collect();
+            let field = GalaxyField::from_galaxies(30, 30, galaxies)
+                .unwrap()
+                .with_row_expansion(rng.gen_range(1..=5))
+                .and_then(|field| field.with_column_expansion(rng.gen_range(1..=5)))
+                .unwrap();
+            let mut expected: u128 = 0;
+            for a in 1..=field.len() {
+                for b in a + 1..=field.len() {
+                    expected += field.distance(a, b).unwrap() as u128;
+                }
+            }
+            assert_eq!(expected, field.sum_of_distances());
+        }
+    }
+
+    #[test]
+    fn expanded_size_is_bounded() {
+        let field = GalaxyField::from_galaxies(3, 3, vec![(0, 0), (2, 2)]).unwrap();
+        let half = u64::MAX / 2;
+        let wide = field.clone().with_row_expansion(half).unwrap();
+        assert!(wide.clone().with_column_expansion(half).is_err());
+        assert!(wide.clone().with_column_factor(1, half).is_err());
+        assert!(field.clone().with_expansion(u64::MAX).is_err());
+        assert!(field.clone().with_row_factor(1, u64::MAX).is_err());
+
+        // the largest distances still fit
+        let wide = wide.with_column_factor(1, half - 10).unwrap();
+        assert_eq!(Some(u64::MAX - 9), wide.distance(1, 2));
+        // shrinking a line makes room again
+        let narrow = wide.with_row_factor(1, 1).unwrap();
+        assert!(narrow.with_column_factor(1, half).is_ok());
+    }
+}
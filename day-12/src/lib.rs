@@ -0,0 +1,606 @@
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Result};
+use rand::Rng;
+
+pub mod nonogram;
+
+// A row of springs, each one operational (`.`), damaged (`#`) or unknown (`?`), together with the
+// sizes of the contiguous groups of damaged springs in it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConditionRecord {
+    springs: Vec<u8>,
+    groups: Vec<usize>,
+}
+
+impl ConditionRecord {
+    pub fn new(springs: &str, groups: Vec<usize>) -> Result<Self> {
+        if let Some(other) = springs.chars().find(|c| !matches!(c, '.' | '#' | '?')) {
+            bail!("Unknown spring condition: {other}");
+        }
+        if groups.contains(&0) {
+            bail!("Groups of damaged springs can't be empty");
+        }
+        Ok(Self {
+            springs: springs.as_bytes().to_vec(),
+            groups,
+        })
+    }
+
+    // a record from springs that are known to be valid
+    pub(crate) fn from_springs(springs: Vec<u8>, groups: Vec<usize>) -> Self {
+        Self { springs, groups }
+    }
+
+    pub fn springs(&self) -> &[u8] {
+        &self.springs
+    }
+
+    pub fn groups(&self) -> &[usize] {
+        &self.groups
+    }
+
+    // `factor` copies of the springs joined by unknown springs, with the groups repeated as well
+    pub fn unfold(&self, factor: usize) -> Self {
+        let mut springs = Vec::with_capacity((self.springs.len() + 1) * factor);
+        for i in 0..factor {
+            if i > 0 {
+                springs.push(b'?');
+            }
+            springs.extend_from_slice(&self.springs);
+        }
+        Self {
+            springs,
+            groups: self.groups.repeat(factor),
+        }
+    }
+
+    // Number of ways to replace the unknown springs so that the groups match. Let ways(i, j) be
+    // the number of arrangements of springs[i..] with groups[j..]. A spring that may be
+    // operational is skipped, giving ways(i + 1, j), and a spring that may be damaged starts
+    // group j if it fits, giving ways(i + size + 1, j + 1). Only two columns of the table are kept,
+    // so this takes O(springs * groups) time and O(springs) memory.
+    pub fn count_arrangements(&self) -> Result<u128> {
+        let operational = self.operational_before();
+        let mut next = self.last_column();
+        for &size in self.groups.iter().rev() {
+            next = self
+                .column(&operational, &next, size, u128::checked_add)
+                .ok_or_else(too_many)?;
+        }
+        Ok(next[0])
+    }
+
+    // whether the springs can be arranged at all
+    pub fn is_feasible(&self) -> bool {
+        let operational = self.operational_before();
+        let mut next = self.last_column();
+        for &size in self.groups.iter().rev() {
+            next = self
+                .column(&operational, &next, size, |a, b| Some(a.saturating_add(b)))
+                .expect("saturating counts never overflow");
+        }
+        next[0] > 0
+    }
+
+    // number of operational springs before every position
+    fn operational_before(&self) -> Vec<usize> {
+        let mut operational = vec![0usize; self.springs.len() + 1];
+        for (i, &spring) in self.springs.iter().enumerate() {
+            operational[i + 1] = operational[i] + (spring == b'.') as usize;
+        }
+        operational
+    }
+
+    // The end of a group of `size` damaged springs starting at `i`, if it fits there. The spring
+    // right after the group has to be operational.
+    fn group_end(&self, operational: &[usize], i: usize, size: usize) -> Option<usize> {
+        let end = i + size;
+        (self.springs[i] != b'.'
+            && end <= self.springs.len()
+            && operational[end] == operational[i]
+            && (end == self.springs.len() || self.springs[end] != b'#'))
+            .then_some(end)
+    }
+
+    // ways(., groups.len()), padded so that a group ending at the last spring can look one past it
+    fn last_column(&self) -> Vec<u128> {
+        let n = self.springs.len();
+        let mut column = vec![1u128; n + 2];
+        for i in (0..n).rev() {
+            column[i] = column[i + 1] * (self.springs[i] != b'#') as u128;
+        }
+        column
+    }
+
+    // ways(., j) from ways(., j + 1), where `size` is the size of group j
+    fn column(
+        &self,
+        operational: &[usize],
+        next: &[u128],
+        size: usize,
+        add: impl Fn(u128, u128) -> Option<u128>,
+    ) -> Option<Vec<u128>> {
+        let n = self.springs.len();
+        let mut current = vec![0u128; n + 2];
+        for i in (0..n).rev() {
+            let mut ways = 0;
+            if self.springs[i] != b'#' {
+                ways = current[i + 1];
+            }
+            if let Some(end) = self.group_end(operational, i, size) {
+                ways = add(ways, next[(end + 1).min(n + 1)])?;
+            }
+            current[i] = ways;
+        }
+        Some(current)
+    }
+
+    // the whole table of ways, `table[j][i]` being ways(i, j)
+    fn table(&self, add: impl Fn(u128, u128) -> Option<u128>) -> Option<Vec<Vec<u128>>> {
+        let operational = self.operational_before();
+        let mut table = vec![self.last_column()];
+        for &size in self.groups.iter().rev() {
+            let next = table.last().expect("last column is always present");
+            table.push(self.column(&operational, next, size, &add)?);
+        }
+        table.reverse();
+        Some(table)
+    }
+
+    // Every valid arrangement, generated lazily by trying an operational spring before a damaged
+    // one at every unknown spring. Branches without arrangements are never entered, so every next
+    // arrangement takes O(springs) steps.
+    pub fn arrangements(&self) -> Arrangements<'_> {
+        let table = self
+            .table(|a, b| Some(a.saturating_add(b)))
+            .expect("saturating counts never overflow");
+        let stack = if table[0][0] > 0 {
+            vec![Frame::new(0, 0)]
+        } else {
+            Vec::new()
+        };
+        Arrangements {
+            record: self,
+            operational: self.operational_before(),
+            current: self.springs.clone(),
+            table,
+            stack,
+        }
+    }
+
+    // One arrangement chosen uniformly at random. Every choice is taken with probability
+    // proportional to the number of arrangements that follow it.
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<String> {
+        let table = self.table(u128::checked_add).ok_or_else(too_many)?;
+        let total = table[0][0];
+        if total == 0 {
+            bail!("Springs can't be arranged to match the groups");
+        }
+
+        let n = self.springs.len();
+        let operational = self.operational_before();
+        let mut chosen = rng.gen_range(0..total);
+        let mut arrangement = self.springs.clone();
+        let (mut i, mut j) = (0, 0);
+        while i < n {
+            let skip = if self.springs[i] != b'#' {
+                table[j][i + 1]
+            } else {
+                0
+            };
+            if chosen < skip {
+                arrangement[i] = b'.';
+                i += 1;
+                continue;
+            }
+            chosen -= skip;
+            let end = self
+                .group_end(&operational, i, self.groups[j])
+                .expect("remaining arrangements start with a group");
+            arrangement[i..end].fill(b'#');
+            if end < n {
+                arrangement[end] = b'.';
+            }
+            i = end + 1;
+            j += 1;
+        }
+        Ok(String::from_utf8(arrangement).expect("springs are ASCII"))
+    }
+
+    // What every unknown spring must be, as positions in the row. Fails if the springs can't be
+    // arranged at all.
+    pub fn resolve(&self) -> Result<Vec<(usize, Resolution)>> {
+        if !self.is_feasible() {
+            bail!("Springs can't be arranged to match the groups");
+        }
+        let mut trial = self.clone();
+        let mut resolutions = Vec::new();
+        for (i, &spring) in self.springs.iter().enumerate() {
+            if spring != b'?' {
+                continue;
+            }
+            trial.springs[i] = b'#';
+            let damaged = trial.is_feasible();
+            trial.springs[i] = b'.';
+            let operational = trial.is_feasible();
+            trial.springs[i] = b'?';
+            let resolution = match (damaged, operational) {
+                (true, false) => Resolution::Damaged,
+                (false, true) => Resolution::Operational,
+                _ => Resolution::Ambiguous,
+            };
+            resolutions.push((i, resolution));
+        }
+        Ok(resolutions)
+    }
+
+    // the same record with every forced unknown spring filled in
+    pub fn repair(&self) -> Result<Self> {
+        let mut repaired = self.clone();
+        for (i, resolution) in self.resolve()? {
+            match resolution {
+                Resolution::Damaged => repaired.springs[i] = b'#',
+                Resolution::Operational => repaired.springs[i] = b'.',
+                Resolution::Ambiguous => (),
+            }
+        }
+        Ok(repaired)
+    }
+}
+
+fn too_many() -> anyhow::Error {
+    anyhow!("Too many arrangements to count")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    Damaged,
+    Operational,
+    // both conditions appear in some arrangement
+    Ambiguous,
+}
+
+// a spring where the arrangement is being decided, and how many choices were tried there
+#[derive(Debug, Clone, Copy)]
+struct Frame {
+    spring: usize,
+    group: usize,
+    tried: u8,
+}
+
+impl Frame {
+    fn new(spring: usize, group: usize) -> Self {
+        Self {
+            spring,
+            group,
+            tried: 0,
+        }
+    }
+}
+
+pub struct Arrangements<'a> {
+    record: &'a ConditionRecord,
+    operational: Vec<usize>,
+    table: Vec<Vec<u128>>,
+    current: Vec<u8>,
+    stack: Vec<Frame>,
+}
+
+impl Iterator for Arrangements<'_> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        let springs = &self.record.springs;
+        let n = springs.len();
+        while let Some(frame) = self.stack.last_mut() {
+            let (i, j) = (frame.spring, frame.group);
+            if i >= n {
+                self.stack.pop();
+                return Some(String::from_utf8(self.current.clone()).expect("springs are ASCII"));
+            }
+
+            frame.tried += 1;
+            match frame.tried {
+                // the spring is operational
+                1 if springs[i] != b'#' && self.table[j][i + 1] > 0 => {
+                    self.current[i] = b'.';
+                    self.stack.push(Frame::new(i + 1, j));
+                }
+                // a group starts at the spring
+                2 => {
+                    let Some(end) = self
+                        .record
+                        .groups
+                        .get(j)
+                        .and_then(|&size| self.record.group_end(&self.operational, i, size))
+                    else {
+                        continue;
+                    };
+                    if self.table[j + 1][(end + 1).min(n + 1)] == 0 {
+                        continue;
+                    }
+                    self.current[i..end].fill(b'#');
+                    if end < n {
+                        self.current[end] = b'.';
+                    }
+                    self.stack.push(Frame::new(end + 1, j + 1));
+                }
+                1 => (),
+                _ => {
+                    self.stack.pop();
+                }
+            }
+        }
+        None
+    }
+}
+
+impl FromStr for ConditionRecord {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (springs, groups) = s
+            .trim()
+            .split_once(' ')
+            .ok_or_else(|| anyhow!("Failed to parse a condition record: {s}"))?;
+        let groups = groups
+            .split(',')
+            .map(|num| {
+                num.parse()
+                    .map_err(|_| anyhow!("Failed to parse a group size: {num}"))
+            })
+            .collect::<Result<Vec<usize>>>()?;
+        Self::new(springs, groups)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet, VecDeque};
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+    use regex::Regex;
+
+    use super::*;
+
+    #[test]
+    fn example_rows() {
+        let rows = [
+            ("???.### 1,1,3", 1, 1),
+            (".??..??...?##. 1,1,3", 4, 16384),
+            ("?#?#?#?#?#?#?#? 1,3,1,6", 1, 1),
+            ("????.#...#... 4,1,1", 1, 16),
+            ("????.######..#####. 1,6,5", 4, 2500),
+            ("?###???????? 3,2,1", 10, 506250),
+        ];
+        for (row, folded, unfolded) in rows {
+            let record: ConditionRecord = row.parse().unwrap();
+            assert_eq!(folded, record.count_arrangements().unwrap());
+            assert_eq!(unfolded, record.unfold(5).count_arrangements().unwrap());
+        }
+        assert!("?#.x 1".parse::<ConditionRecord>().is_err());
+        assert!("?#. 0".parse::<ConditionRecord>().is_err());
+    }
+
+    #[test]
+    fn large_unfold_factors() {
+        // every copy but the first one gains a choice of 8 arrangements
+        let record: ConditionRecord = ".??..??...?##. 1,1,3".parse().unwrap();
+        assert_eq!(
+            4 * 8u128.pow(29),
+            record.unfold(30).count_arrangements().unwrap()
+        );
+
+        // a single arrangement no matter how far the row is unfolded
+        let record: ConditionRecord = "?#?#?#?#?#?#?#? 1,3,1,6".parse().unwrap();
+        assert_eq!(1, record.unfold(200).count_arrangements().unwrap());
+
+        // far too many arrangements for u128
+        let record: ConditionRecord = "???? 1".parse().unwrap();
+        assert!(record.unfold(200).count_arrangements().is_err());
+    }
+
+    #[test]
+    fn matches_recursive_count() {
+        let mut rng = StdRng::seed_from_u64(12);
+        for _ in 0..2000 {
+            let springs: String = (0..rng.gen_range(1..=14))
+                .map(|_| ['.', '#', '?', '?'][rng.gen_range(0..4)])
+                .collect();
+            let groups: Vec<usize> = (0..rng.gen_range(1..=4))
+                .map(|_| rng.gen_range(1..=4))
+                .collect();
+            let record = ConditionRecord::new(&springs, groups.clone()).unwrap();
+            assert_eq!(
+                count_arrangements(springs.clone(), groups.into()) as u128,
+                record.count_arrangements().unwrap(),
+                "{springs}"
+            );
+        }
+    }
+
+    #[test]
+    fn enumerate_arrangements() {
+        let record: ConditionRecord = "?###???????? 3,2,1".parse().unwrap();
+        let arrangements: Vec<String> = record.arrangements().collect();
+        assert_eq!(10, arrangements.len());
+        assert_eq!(".###....##.#", arrangements[0]);
+        assert_eq!(".###.##.#...", arrangements[9]);
+        let unique: HashSet<&String> = arrangements.iter().collect();
+        assert_eq!(10, unique.len());
+        for arrangement in arrangements.iter() {
+            let concrete = ConditionRecord::new(arrangement, vec![3, 2, 1]).unwrap();
+            assert_eq!(1, concrete.count_arrangements().unwrap());
+        }
+
+        let record: ConditionRecord = "#.# 3".parse().unwrap();
+        assert_eq!(None, record.arrangements().next());
+
+        // far too many to count, but still easy to list a few
+        let record: ConditionRecord = "???? 1".parse::<ConditionRecord>().unwrap().unfold(200);
+        assert_eq!(5, record.arrangements().take(5).count());
+    }
+
+    #[test]
+    fn uniform_samples() {
+        let record: ConditionRecord = "?###???????? 3,2,1".parse().unwrap();
+        let mut rng = StdRng::seed_from_u64(2023);
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        for _ in 0..10_000 {
+            *seen.entry(record.sample(&mut rng).unwrap()).or_default() += 1;
+        }
+        let expected: HashSet<String> = record.arrangements().collect();
+        assert_eq!(expected, seen.keys().cloned().collect());
+        // every arrangement is drawn close to 1000 times
+        assert!(seen.values().all(|&count| (850..1150).contains(&count)));
+
+        let record: ConditionRecord = "#.# 3".parse().unwrap();
+        assert!(record.sample(&mut rng).is_err());
+    }
+
+    #[test]
+    fn forced_springs() {
+        let record: ConditionRecord = "?###???????? 3,2,1".parse().unwrap();
+        let resolutions = record.resolve().unwrap();
+        assert_eq!((0, Resolution::Operational), resolutions[0]);
+        assert_eq!((4, Resolution::Operational), resolutions[1]);
+        assert!(resolutions[2..]
+            .iter()
+            .all(|&(_, r)| r == Resolution::Ambiguous));
+
+        let record: ConditionRecord = "??.?#?? 2,3".parse().unwrap();
+        let repaired = record.repair().unwrap();
+        assert_eq!(b"##.?##?", repaired.springs());
+        assert!("#.# 3"
+            .parse::<ConditionRecord>()
+            .unwrap()
+            .resolve()
+            .is_err());
+    }
+
+    // the recursive counter the DP replaced, placing one group after another
+    fn count_arrangements(pattern: String, counts: VecDeque<usize>) -> usize {
+        let total_count = counts.iter().sum();
+        do_count_arrangements(pattern.trim_matches('.').to_owned(), counts, total_count)
+    }
+
+    fn do_count_arrangements(
+        pattern: String,
+        mut counts: VecDeque<usize>,
+        total_count: usize,
+    ) -> usize {
+        if pattern.is_empty() || counts.is_empty() {
+            return 0;
+        }
+
+        if let Some(count) = counts.pop_front() {
+            let variants: Vec<String> = find_clusters(&pattern)
+                .into_iter()
+                .filter_map(|cluster| {
+                    let replaced_patterns: Vec<String> =
+                        generate_replaced_patterns(&pattern, cluster, count)
+                            .into_iter()
+                            .filter(|p| {
+                                p.chars().filter(|c| *c == '#' || *c == '?').count()
+                                    >= total_count - count
+                            })
+                            .collect();
+
+                    if replaced_patterns.is_empty() {
+                        return None;
+                    }
+                    Some(replaced_patterns)
+                })
+                .flatten()
+                .collect();
+
+            let result = if counts.is_empty() {
+                variants
+                    .into_iter()
+                    .filter(|p| p.chars().filter(|c| *c == '#').count() == total_count - count)
+                    .count()
+            } else {
+                variants.into_iter().fold(0, |total, replaced_pattern| {
+                    total
+                        + do_count_arrangements(
+                            replaced_pattern,
+                            counts.clone(),
+                            total_count - count,
+                        )
+                })
+            };
+
+            return result;
+        }
+        panic!("Something went wrong!");
+    }
+
+    fn generate_replaced_patterns(
+        pattern: &str,
+        cluster: Cluster,
+        hash_count: usize,
+    ) -> Vec<String> {
+        let mut replaced_patterns = Vec::new();
+
+        if cluster.length >= hash_count {
+            for i in 0..(cluster.length - hash_count + 1) {
+                let hash_range_start = cluster.start + i;
+                let hash_range_end = cluster.start + i + hash_count;
+
+                if hash_range_start > 0
+                    && pattern[..hash_range_start]
+                        .chars()
+                        .filter(|c| *c == '#')
+                        .count()
+                        != 0
+                {
+                    // second condition here makes sure that we do not skip any cluster of '#'-s, while
+                    // matching
+                    continue;
+                }
+
+                if hash_range_end < pattern.len() {
+                    if let Some(after_replacement) = pattern.chars().nth(hash_range_end) {
+                        if after_replacement == '#' {
+                            // if after matching we discover that the next character is '#', we
+                            // overflow the allowed has_count by at least 1
+                            continue;
+                        }
+                    }
+                }
+
+                let mut replaced_pattern = String::new();
+                if hash_range_end < pattern.len() {
+                    // pass on the leftover string after substitution of has_count '#'-s
+                    replaced_pattern.push_str(&pattern[hash_range_end + 1..]);
+                }
+                replaced_patterns.push(replaced_pattern);
+            }
+        }
+
+        replaced_patterns
+    }
+
+    #[derive(Debug, Clone, Copy)]
+    struct Cluster {
+        start: usize,
+        length: usize,
+    }
+
+    fn find_clusters(pattern: &str) -> Vec<Cluster> {
+        let mut clusters = Vec::new();
+        if !pattern.is_empty() {
+            let re = Regex::new(r"[#?]+").unwrap();
+            clusters = re
+                .find_iter(pattern)
+                .map(|m| Cluster {
+                    start: m.start(),
+                    length: m.len(),
+                })
+                .collect();
+        }
+
+        clusters
+    }
+}
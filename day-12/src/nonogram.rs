@@ -0,0 +1,317 @@
+use std::{collections::VecDeque, fmt, str::FromStr};
+
+use anyhow::{anyhow, bail, Result};
+
+use crate::{ConditionRecord, Resolution};
+
+// A rectangular puzzle where every row and column is a line of springs with known groups of
+// damaged ones, i.e. a condition record without any known spring.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Nonogram {
+    rows: Vec<Vec<usize>>,
+    columns: Vec<Vec<usize>>,
+}
+
+// cells are `#` (filled), `.` (empty) or `?` (not decided yet)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid {
+    width: usize,
+    cells: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Solutions {
+    None,
+    Unique(Grid),
+    // two different solutions, there may be more
+    Multiple(Grid, Grid),
+}
+
+impl Grid {
+    fn unknown(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            cells: vec![b'?'; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.cells.len().checked_div(self.width).unwrap_or(0)
+    }
+
+    // whether a cell is filled, `None` while it is not decided
+    pub fn get(&self, row: usize, column: usize) -> Option<bool> {
+        match self.cells[row * self.width + column] {
+            b'#' => Some(true),
+            b'.' => Some(false),
+            _ => None,
+        }
+    }
+
+    pub fn is_solved(&self) -> bool {
+        !self.cells.contains(&b'?')
+    }
+
+    fn row(&self, row: usize) -> Vec<u8> {
+        self.cells[row * self.width..(row + 1) * self.width].to_vec()
+    }
+
+    fn column(&self, column: usize) -> Vec<u8> {
+        self.cells
+            .iter()
+            .skip(column)
+            .step_by(self.width)
+            .copied()
+            .collect()
+    }
+}
+
+impl fmt::Display for Grid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.cells.chunks(self.width) {
+            writeln!(f, "{}", String::from_utf8_lossy(row))?;
+        }
+        Ok(())
+    }
+}
+
+// a row or a column of the grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Line {
+    Row(usize),
+    Column(usize),
+}
+
+impl Nonogram {
+    pub fn new(rows: Vec<Vec<usize>>, columns: Vec<Vec<usize>>) -> Result<Self> {
+        if rows
+            .iter()
+            .chain(columns.iter())
+            .any(|clue| clue.contains(&0))
+        {
+            bail!("Clues can't contain empty groups");
+        }
+        Ok(Self { rows, columns })
+    }
+
+    pub fn width(&self) -> usize {
+        self.columns.len()
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    // Solves the puzzle, looking for a second solution to tell whether it is unique. Every line is
+    // propagated with the forced cells of its condition record, and when that stalls, the first
+    // undecided cell is guessed both ways.
+    pub fn solve(&self) -> Solutions {
+        let mut found = Vec::new();
+        let total = |clues: &[Vec<usize>]| clues.iter().flatten().sum::<usize>();
+        if total(&self.rows) == total(&self.columns) {
+            self.search(Grid::unknown(self.width(), self.height()), &mut found);
+        }
+
+        let mut found = found.into_iter();
+        match (found.next(), found.next()) {
+            (None, _) => Solutions::None,
+            (Some(grid), None) => Solutions::Unique(grid),
+            (Some(first), Some(second)) => Solutions::Multiple(first, second),
+        }
+    }
+
+    fn search(&self, mut grid: Grid, found: &mut Vec<Grid>) {
+        let lines = (0..self.height())
+            .map(Line::Row)
+            .chain((0..self.width()).map(Line::Column));
+        if self.propagate(&mut grid, lines.collect()).is_err() {
+            return;
+        }
+        let Some(cell) = grid.cells.iter().position(|&c| c == b'?') else {
+            found.push(grid);
+            return;
+        };
+
+        for guess in [b'#', b'.'] {
+            if found.len() >= 2 {
+                return;
+            }
+            let mut guessed = grid.clone();
+            guessed.cells[cell] = guess;
+            self.search(guessed, found);
+        }
+    }
+
+    // Applies the forced cells of every queued line until nothing changes. Lines crossing a changed
+    // cell are queued again. Fails when a line can't be arranged anymore.
+    fn propagate(&self, grid: &mut Grid, mut queue: VecDeque<Line>) -> Result<()> {
+        let mut queued: Vec<Line> = queue.iter().copied().collect();
+        while let Some(line) = queue.pop_front() {
+            queued.retain(|&l| l != line);
+            let (cells, clue) = match line {
+                Line::Row(r) => (grid.row(r), &self.rows[r]),
+                Line::Column(c) => (grid.column(c), &self.columns[c]),
+            };
+            let record = ConditionRecord::from_springs(cells, clue.clone());
+            for (i, resolution) in record.resolve()? {
+                let value = match resolution {
+                    Resolution::Damaged => b'#',
+                    Resolution::Operational => b'.',
+                    Resolution::Ambiguous => continue,
+                };
+                let (cell, crossing) = match line {
+                    Line::Row(r) => (r * grid.width + i, Line::Column(i)),
+                    Line::Column(c) => (i * grid.width + c, Line::Row(i)),
+                };
+                grid.cells[cell] = value;
+                if !queued.contains(&crossing) {
+                    queued.push(crossing);
+                    queue.push_back(crossing);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// Reads the `.non` format: `width` and `height` lines, followed by `rows` and `columns` sections
+// with one comma separated clue per line, where `0` or an empty line stands for an empty line of
+// the grid. Any other keys, like `title` or `goal`, are ignored.
+impl FromStr for Nonogram {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut width: Option<usize> = None;
+        let mut height: Option<usize> = None;
+        let mut rows: Option<Vec<Vec<usize>>> = None;
+        let mut columns: Option<Vec<Vec<usize>>> = None;
+
+        let mut lines = s.lines().map(|l| l.trim());
+        while let Some(line) = lines.next() {
+            let (key, value) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            let size = || -> Result<usize> {
+                value
+                    .trim()
+                    .parse()
+                    .map_err(|_| anyhow!("Failed to parse a size: {value}"))
+            };
+            match key {
+                "width" => width = Some(size()?),
+                "height" => height = Some(size()?),
+                "rows" | "columns" => {
+                    let count = if key == "rows" { height } else { width }
+                        .ok_or_else(|| anyhow!("Size must be given before the {key}"))?;
+                    let clues = (0..count)
+                        .map(|_| {
+                            let clue = lines
+                                .next()
+                                .ok_or_else(|| anyhow!("Not enough {key} clues"))?;
+                            parse_clue(clue)
+                        })
+                        .collect::<Result<Vec<Vec<usize>>>>()?;
+                    if key == "rows" {
+                        rows = Some(clues);
+                    } else {
+                        columns = Some(clues);
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        Nonogram::new(
+            rows.ok_or_else(|| anyhow!("No row clues found"))?,
+            columns.ok_or_else(|| anyhow!("No column clues found"))?,
+        )
+    }
+}
+
+fn parse_clue(clue: &str) -> Result<Vec<usize>> {
+    clue.split(',')
+        .map(|num| num.trim())
+        .filter(|num| !num.is_empty() && *num != "0")
+        .map(|num| {
+            num.parse()
+                .map_err(|_| anyhow!("Failed to parse a clue: {clue}"))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unique_solution() {
+        let puzzle = r#"
+            title "Heart"
+            width 5
+            height 5
+
+            rows
+            1,1
+            5
+            5
+            3
+            1
+
+            columns
+            2
+            4
+            4
+            4
+            2
+        "#;
+        let nonogram: Nonogram = puzzle.parse().unwrap();
+        let Solutions::Unique(grid) = nonogram.solve() else {
+            panic!("expected a unique solution");
+        };
+        assert!(grid.is_solved());
+        assert_eq!(".#.#.\n#####\n#####\n.###.\n..#..\n", grid.to_string());
+        assert_eq!(Some(true), grid.get(4, 2));
+        assert_eq!(Some(false), grid.get(0, 0));
+    }
+
+    #[test]
+    fn needs_guessing() {
+        // a diagonal can go either way
+        let nonogram = Nonogram::new(vec![vec![1], vec![1]], vec![vec![1], vec![1]]).unwrap();
+        let Solutions::Multiple(first, second) = nonogram.solve() else {
+            panic!("expected multiple solutions");
+        };
+        assert_ne!(first, second);
+        assert_eq!("#.\n.#\n", first.to_string());
+
+        // propagation alone stalls, but only one guess survives
+        let nonogram = Nonogram::new(
+            vec![vec![1], vec![1, 1], vec![1]],
+            vec![vec![1], vec![1, 1], vec![1]],
+        )
+        .unwrap();
+        let Solutions::Unique(grid) = nonogram.solve() else {
+            panic!("expected a unique solution");
+        };
+        assert_eq!(".#.\n#.#\n.#.\n", grid.to_string());
+    }
+
+    #[test]
+    fn no_solution() {
+        let nonogram = Nonogram::new(vec![vec![2], vec![]], vec![vec![1], vec![]]).unwrap();
+        assert_eq!(Solutions::None, nonogram.solve());
+        let nonogram = Nonogram::new(
+            vec![vec![3], vec![], vec![1]],
+            vec![vec![1], vec![1], vec![2]],
+        )
+        .unwrap();
+        assert_eq!(Solutions::None, nonogram.solve());
+        let nonogram = Nonogram::new(vec![vec![1, 1], vec![]], vec![vec![1], vec![1]]).unwrap();
+        assert_eq!(Solutions::None, nonogram.solve());
+
+        assert!("width 2\nheight 1\nrows\n3\n".parse::<Nonogram>().is_err());
+        assert!("rows\n1".parse::<Nonogram>().is_err());
+    }
+}
@@ -0,0 +1,293 @@
+use std::str::FromStr;
+
+use anyhow::{bail, Result};
+
+// (row, column)
+pub type Pos = (usize, usize);
+
+pub mod symmetry;
+
+const WORD: usize = u64::BITS as usize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    // a mirror line between two rows
+    Horizontal,
+    // a mirror line between two columns
+    Vertical,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tolerance {
+    Exactly(usize),
+    AtMost(usize),
+}
+
+impl Tolerance {
+    fn max(&self) -> usize {
+        match *self {
+            Tolerance::Exactly(k) | Tolerance::AtMost(k) => k,
+        }
+    }
+
+    fn accepts(&self, mismatches: usize) -> bool {
+        match *self {
+            Tolerance::Exactly(k) => mismatches == k,
+            Tolerance::AtMost(k) => mismatches <= k,
+        }
+    }
+}
+
+// A mirror line with `before` rows or columns on its top or left side. Every mismatch is a pair of
+// cells that should be equal, but are not; fixing either of them removes the mismatch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reflection {
+    pub axis: Axis,
+    pub before: usize,
+    pub mismatches: Vec<(Pos, Pos)>,
+}
+
+impl Reflection {
+    // 100 for every row above a horizontal line, 1 for every column left of a vertical one
+    pub fn summary(&self) -> usize {
+        match self.axis {
+            Axis::Horizontal => 100 * self.before,
+            Axis::Vertical => self.before,
+        }
+    }
+}
+
+// A pattern of ash (`.`) and rocks (`#`). Every row and every column is kept as a bitmask of
+// rocks, so comparing two lines is a handful of XORs and popcounts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Terrain {
+    width: usize,
+    height: usize,
+    rows: Vec<Vec<u64>>,
+    columns: Vec<Vec<u64>>,
+}
+
+impl Terrain {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn is_rock(&self, (row, column): Pos) -> bool {
+        self.rows[row][column / WORD] & (1 << (column % WORD)) != 0
+    }
+
+    pub(crate) fn lines(&self, axis: Axis) -> &[Vec<u64>] {
+        match axis {
+            Axis::Horizontal => &self.rows,
+            Axis::Vertical => &self.columns,
+        }
+    }
+
+    // every mirror line whose number of mismatches is accepted by the tolerance, horizontal ones
+    // first
+    pub fn reflections(&self, tolerance: Tolerance) -> Vec<Reflection> {
+        [Axis::Horizontal, Axis::Vertical]
+            .into_iter()
+            .flat_map(|axis| {
+                let lines = self.lines(axis);
+                (1..lines.len())
+                    .filter(move |&before| {
+                        self.count_mismatches(axis, before, tolerance.max())
+                            .is_some_and(|count| tolerance.accepts(count))
+                    })
+                    .map(move |before| Reflection {
+                        axis,
+                        before,
+                        mismatches: self.mismatches(axis, before),
+                    })
+            })
+            .collect()
+    }
+
+    // the mirror line with exactly `k` mismatches, if there is only one
+    pub fn reflection(&self, k: usize) -> Option<Reflection> {
+        let mut reflections = self.reflections(Tolerance::Exactly(k));
+        (reflections.len() == 1).then(|| reflections.remove(0))
+    }
+
+    // pairs of lines that are mirrored into each other by a line with `before` lines above it
+    fn pairs(len: usize, before: usize) -> impl Iterator<Item = (usize, usize)> {
+        let overlap = before.min(len - before);
+        (0..overlap).map(move |i| (before - 1 - i, before + i))
+    }
+
+    // number of mismatches, or `None` as soon as there are more than `limit`
+    fn count_mismatches(&self, axis: Axis, before: usize, limit: usize) -> Option<usize> {
+        let lines = self.lines(axis);
+        let mut count = 0;
+        for (a, b) in Self::pairs(lines.len(), before) {
+            count += lines[a]
+                .iter()
+                .zip(lines[b].iter())
+                .map(|(x, y)| (x ^ y).count_ones() as usize)
+                .sum::<usize>();
+            if count > limit {
+                return None;
+            }
+        }
+        Some(count)
+    }
+
+    fn mismatches(&self, axis: Axis, before: usize) -> Vec<(Pos, Pos)> {
+        let lines = self.lines(axis);
+        let mut mismatches = Vec::new();
+        for (a, b) in Self::pairs(lines.len(), before) {
+            for (w, (x, y)) in lines[a].iter().zip(lines[b].iter()).enumerate() {
+                let mut diff = x ^ y;
+                while diff != 0 {
+                    let i = w * WORD + diff.trailing_zeros() as usize;
+                    diff &= diff - 1;
+                    mismatches.push(match axis {
+                        Axis::Horizontal => ((a, i), (b, i)),
+                        Axis::Vertical => ((i, a), (i, b)),
+                    });
+                }
+            }
+        }
+        mismatches.sort();
+        mismatches
+    }
+}
+
+impl FromStr for Terrain {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let lines: Vec<&str> = s
+            .lines()
+            .map(|l| l.trim())
+            .filter(|l| !l.is_empty())
+            .collect();
+        let height = lines.len();
+        let width = lines.first().map_or(0, |l| l.len());
+        if width == 0 {
+            bail!("Terrain is empty");
+        }
+
+        let mut rows = vec![vec![0u64; width.div_ceil(WORD)]; height];
+        let mut columns = vec![vec![0u64; height.div_ceil(WORD)]; width];
+        for (r, line) in lines.iter().enumerate() {
+            if line.len() != width {
+                bail!("Terrain rows have different lengths");
+            }
+            for (c, symbol) in line.chars().enumerate() {
+                match symbol {
+                    '#' => {
+                        rows[r][c / WORD] |= 1 << (c % WORD);
+                        columns[c][r / WORD] |= 1 << (r % WORD);
+                    }
+                    '.' => (),
+                    other => bail!("Unknown terrain symbol: {other}"),
+                }
+            }
+        }
+
+        Ok(Self {
+            width,
+            height,
+            rows,
+            columns,
+        })
+    }
+}
+
+// terrains are separated by empty lines
+pub fn parse_terrains(input: &str) -> Result<Vec<Terrain>> {
+    let mut terrains = Vec::new();
+    let mut block: Vec<&str> = Vec::new();
+    for line in input.lines().map(|l| l.trim()).chain([""]) {
+        if !line.is_empty() {
+            block.push(line);
+        } else if !block.is_empty() {
+            terrains.push(block.join("\n").parse()?);
+            block.clear();
+        }
+    }
+    Ok(terrains)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = r#"
+        #.##..##.
+        ..#.##.#.
+        ##......#
+        ##......#
+        ..#.##.#.
+        ..##..##.
+        #.#.##.#.
+
+        #...##..#
+        #....#..#
+        ..##..###
+        #####.##.
+        #####.##.
+        ..##..###
+        #....#..#
+    "#;
+
+    #[test]
+    fn smudges() {
+        let terrains = parse_terrains(EXAMPLE).unwrap();
+        assert_eq!(2, terrains.len());
+
+        let clean = terrains[0].reflection(0).unwrap();
+        assert_eq!((Axis::Vertical, 5), (clean.axis, clean.before));
+        assert!(clean.mismatches.is_empty());
+
+        let smudged = terrains[0].reflection(1).unwrap();
+        assert_eq!((Axis::Horizontal, 3), (smudged.axis, smudged.before));
+        assert_eq!(vec![((0, 0), (5, 0))], smudged.mismatches);
+        assert_eq!(300, smudged.summary());
+
+        let smudged = terrains[1].reflection(1).unwrap();
+        assert_eq!(vec![((0, 4), (1, 4))], smudged.mismatches);
+        assert_eq!(100, smudged.summary());
+
+        let at_most_one = terrains[1].reflections(Tolerance::AtMost(1));
+        assert_eq!(
+            vec![1, 4],
+            at_most_one.iter().map(|r| r.before).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn wide_patterns() {
+        // 150 columns need three words per row
+        let row = |smudge: Option<usize>| -> String {
+            (0..150)
+                .map(|c| {
+                    if Some(c) == smudge || c % 7 == 0 {
+                        '#'
+                    } else {
+                        '.'
+                    }
+                })
+                .collect()
+        };
+        let pattern = [row(None), row(Some(130)), row(None), row(None)].join("\n");
+        let terrain: Terrain = pattern.parse().unwrap();
+        assert!(terrain.is_rock((1, 130)));
+
+        let horizontal: Vec<Reflection> = terrain
+            .reflections(Tolerance::AtMost(2))
+            .into_iter()
+            .filter(|r| r.axis == Axis::Horizontal)
+            .collect();
+        assert_eq!(3, horizontal.len());
+        assert_eq!(vec![((0, 130), (1, 130))], horizontal[0].mismatches);
+        assert_eq!(vec![((1, 130), (2, 130))], horizontal[1].mismatches);
+        assert!(horizontal[2].mismatches.is_empty());
+    }
+}
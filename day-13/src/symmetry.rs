@@ -0,0 +1,327 @@
+use crate::{Axis, Pos, Terrain, Tolerance};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Diagonal {
+    // from the top left to the bottom right
+    Main,
+    // from the top right to the bottom left
+    Anti,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymmetryKind {
+    // a mirror line between rows or columns
+    Mirror(Axis),
+    // a mirror along the diagonal of a square region
+    DiagonalMirror(Diagonal),
+    // the region looks the same after turning it by 180 degrees
+    HalfTurn,
+    // the region looks the same after turning it by 90 degrees
+    QuarterTurn,
+    // the region looks the same when shifted by this many rows and columns, as far as it overlaps
+    Translation { rows: usize, columns: usize },
+}
+
+// rows `top..top + height` and columns `left..left + width`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    pub top: usize,
+    pub left: usize,
+    pub height: usize,
+    pub width: usize,
+}
+
+impl Region {
+    // centre of the region in half cells, i.e. with doubled coordinates, so that a centre between
+    // two cells is still a whole number
+    pub fn centre(&self) -> Pos {
+        (
+            2 * self.top + self.height - 1,
+            2 * self.left + self.width - 1,
+        )
+    }
+}
+
+// A symmetry of a terrain, together with the region where it holds. The centre lies on the axis of
+// a mirror, and is the pivot of a rotation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Symmetry {
+    pub kind: SymmetryKind,
+    pub extent: Region,
+}
+
+impl Symmetry {
+    pub fn centre(&self) -> Pos {
+        self.extent.centre()
+    }
+}
+
+impl Terrain {
+    fn whole(&self) -> Region {
+        Region {
+            top: 0,
+            left: 0,
+            height: self.height(),
+            width: self.width(),
+        }
+    }
+
+    // every kind of symmetry the terrain has, diagonal mirrors in squares of at least `min_size`
+    pub fn symmetries(&self, min_size: usize) -> Vec<Symmetry> {
+        let mut symmetries = self.mirrors();
+        symmetries.extend(self.diagonal_mirrors(min_size));
+        symmetries.extend(self.rotations());
+        symmetries.extend(self.translations());
+        symmetries
+    }
+
+    // perfect mirror lines, covering the rows or columns that are reflected into each other
+    pub fn mirrors(&self) -> Vec<Symmetry> {
+        self.reflections(Tolerance::Exactly(0))
+            .into_iter()
+            .map(|reflection| {
+                let len = match reflection.axis {
+                    Axis::Horizontal => self.height(),
+                    Axis::Vertical => self.width(),
+                };
+                let overlap = reflection.before.min(len - reflection.before);
+                let start = reflection.before - overlap;
+                let mut extent = self.whole();
+                match reflection.axis {
+                    Axis::Horizontal => (extent.top, extent.height) = (start, 2 * overlap),
+                    Axis::Vertical => (extent.left, extent.width) = (start, 2 * overlap),
+                }
+                Symmetry {
+                    kind: SymmetryKind::Mirror(reflection.axis),
+                    extent,
+                }
+            })
+            .collect()
+    }
+
+    // For every diagonal line of the terrain, the largest square with its own diagonal on that
+    // line that is mirrored by it. Squares smaller than `min_size` are left out.
+    pub fn diagonal_mirrors(&self, min_size: usize) -> Vec<Symmetry> {
+        let (height, width) = (self.height(), self.width());
+        let mut symmetries = Vec::new();
+        for diagonal in [Diagonal::Main, Diagonal::Anti] {
+            let mut best: Vec<Option<Region>> = vec![None; height + width];
+            for top in 0..height {
+                for column in 0..width {
+                    let size = self.mirrored_square(diagonal, top, column);
+                    if size < min_size.max(2) {
+                        continue;
+                    }
+                    // squares on the same line share `column - top` for main diagonals, and
+                    // `column + top` for anti-diagonals
+                    let (line, left) = match diagonal {
+                        Diagonal::Main => (column + height - top, column),
+                        Diagonal::Anti => (column + top, column + 1 - size),
+                    };
+                    let slot = &mut best[line];
+                    if slot.is_none_or(|region| region.height < size) {
+                        *slot = Some(Region {
+                            top,
+                            left,
+                            height: size,
+                            width: size,
+                        });
+                    }
+                }
+            }
+            symmetries.extend(best.into_iter().flatten().map(|extent| Symmetry {
+                kind: SymmetryKind::DiagonalMirror(diagonal),
+                extent,
+            }));
+        }
+        symmetries
+    }
+
+    // Size of the largest mirrored square hanging from a corner on its diagonal: the top left one
+    // for main diagonals and the top right one for anti-diagonals. In coordinates relative to the
+    // corner both mirrors swap (i, j) with (j, i), and all these squares are nested, so a square
+    // of size s + 1 is mirrored if the one of size s is and its new row mirrors its new column.
+    fn mirrored_square(&self, diagonal: Diagonal, top: usize, column: usize) -> usize {
+        let (max, cell): (usize, Box<dyn Fn(usize, usize) -> Pos>) = match diagonal {
+            Diagonal::Main => (
+                (self.height() - top).min(self.width() - column),
+                Box::new(|i, j| (top + i, column + j)),
+            ),
+            Diagonal::Anti => (
+                (self.height() - top).min(column + 1),
+                Box::new(|i, j| (top + i, column - j)),
+            ),
+        };
+        let mut size = 1;
+        while size < max
+            && (0..size).all(|j| self.is_rock(cell(size, j)) == self.is_rock(cell(j, size)))
+        {
+            size += 1;
+        }
+        size
+    }
+
+    // half and quarter turns of the whole terrain
+    pub fn rotations(&self) -> Vec<Symmetry> {
+        let (height, width) = (self.height(), self.width());
+        let half_turn = (0..height).all(|r| {
+            (0..width)
+                .all(|c| self.is_rock((r, c)) == self.is_rock((height - 1 - r, width - 1 - c)))
+        });
+        let quarter_turn = height == width
+            && (0..height).all(|r| {
+                (0..width).all(|c| self.is_rock((r, c)) == self.is_rock((height - 1 - c, r)))
+            });
+
+        let mut symmetries = Vec::new();
+        if half_turn {
+            symmetries.push(Symmetry {
+                kind: SymmetryKind::HalfTurn,
+                extent: self.whole(),
+            });
+        }
+        if quarter_turn {
+            symmetries.push(Symmetry {
+                kind: SymmetryKind::QuarterTurn,
+                extent: self.whole(),
+            });
+        }
+        symmetries
+    }
+
+    // the shortest period of the rows and of the columns, if the terrain repeats at all; a period
+    // only counts when the pattern fits at least twice, otherwise equal first and last lines would
+    // already make any terrain periodic
+    pub fn translations(&self) -> Vec<Symmetry> {
+        let period = |lines: &[Vec<u64>]| -> Option<usize> {
+            (1..=lines.len() / 2).find(|&p| (0..lines.len() - p).all(|i| lines[i] == lines[i + p]))
+        };
+        let mut symmetries = Vec::new();
+        if let Some(rows) = period(self.lines(Axis::Horizontal)) {
+            symmetries.push(Symmetry {
+                kind: SymmetryKind::Translation { rows, columns: 0 },
+                extent: self.whole(),
+            });
+        }
+        if let Some(columns) = period(self.lines(Axis::Vertical)) {
+            symmetries.push(Symmetry {
+                kind: SymmetryKind::Translation { rows: 0, columns },
+                extent: self.whole(),
+            });
+        }
+        symmetries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(symmetries: &[Symmetry]) -> Vec<SymmetryKind> {
+        symmetries.iter().map(|s| s.kind).collect()
+    }
+
+    #[test]
+    fn diagonal_mirrors() {
+        let terrain: Terrain = r#"
+            #.#..
+            ..##.
+            ##.#.
+            .##..
+        "#
+        .parse()
+        .unwrap();
+        let mirrors = terrain.diagonal_mirrors(3);
+        assert_eq!(
+            vec![
+                Symmetry {
+                    kind: SymmetryKind::DiagonalMirror(Diagonal::Main),
+                    extent: Region {
+                        top: 0,
+                        left: 0,
+                        height: 4,
+                        width: 4,
+                    },
+                },
+                Symmetry {
+                    kind: SymmetryKind::DiagonalMirror(Diagonal::Anti),
+                    extent: Region {
+                        top: 1,
+                        left: 1,
+                        height: 3,
+                        width: 3,
+                    },
+                },
+            ],
+            mirrors
+        );
+        assert_eq!((3, 3), mirrors[0].centre());
+        assert_eq!((4, 4), mirrors[1].centre());
+    }
+
+    #[test]
+    fn rotations() {
+        let pinwheel: Terrain = "##.\n...\n.##\n".parse().unwrap();
+        assert_eq!(vec![SymmetryKind::HalfTurn], kinds(&pinwheel.rotations()));
+
+        let pinwheel: Terrain = "##.#\n...#\n#...\n#.##\n".parse().unwrap();
+        let rotations = pinwheel.rotations();
+        assert_eq!(
+            vec![SymmetryKind::HalfTurn, SymmetryKind::QuarterTurn],
+            kinds(&rotations)
+        );
+        assert_eq!((3, 3), rotations[1].centre());
+
+        let arrow: Terrain = ".#.\n###\n".parse().unwrap();
+        assert!(arrow.rotations().is_empty());
+    }
+
+    #[test]
+    fn translations_and_mirrors() {
+        let terrain: Terrain = r#"
+            #..#..#.
+            .#..#..#
+            #..#..#.
+            .#..#..#
+            #..#..#.
+        "#
+        .parse()
+        .unwrap();
+        assert_eq!(
+            vec![
+                SymmetryKind::Translation {
+                    rows: 2,
+                    columns: 0
+                },
+                SymmetryKind::Translation {
+                    rows: 0,
+                    columns: 3
+                },
+            ],
+            kinds(&terrain.translations())
+        );
+
+        // alternating rows never sit next to an equal one, so periodicity is all there is
+        assert!(terrain.mirrors().is_empty());
+        assert_eq!(2, terrain.symmetries(4).len());
+
+        // equal first and last rows alone are no period
+        let terrain: Terrain = "#..\n.#.\n..#\n#..\n".parse().unwrap();
+        assert!(terrain.translations().is_empty());
+
+        let terrain: Terrain = "#..#.\n.##..\n".parse().unwrap();
+        let mirrors = terrain.mirrors();
+        assert_eq!(1, mirrors.len());
+        assert_eq!(SymmetryKind::Mirror(Axis::Vertical), mirrors[0].kind);
+        assert_eq!(
+            Region {
+                top: 0,
+                left: 0,
+                height: 2,
+                width: 4,
+            },
+            mirrors[0].extent
+        );
+        assert_eq!((1, 3), mirrors[0].centre());
+    }
+}
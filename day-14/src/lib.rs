@@ -0,0 +1,515 @@
+use std::{fmt, str::FromStr};
+
+use anyhow::{bail, Result};
+
+pub mod program;
+pub mod timeline;
+
+pub use program::Program;
+use program::Step;
+pub use timeline::Timeline;
+
+// (row, column)
+pub type Pos = (usize, usize);
+
+const WORD: usize = u64::BITS as usize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    North,
+    West,
+    South,
+    East,
+}
+
+impl Direction {
+    // the order of a single spin cycle
+    pub const CYCLE: [Direction; 4] = [
+        Direction::North,
+        Direction::West,
+        Direction::South,
+        Direction::East,
+    ];
+
+    // the rocks roll along columns for north and south, and along rows for west and east
+    fn along_columns(&self) -> bool {
+        matches!(self, Direction::North | Direction::South)
+    }
+
+    // whether the rocks roll towards the lower indices of their row or column
+    fn towards_start(&self) -> bool {
+        matches!(self, Direction::North | Direction::West)
+    }
+}
+
+impl TryFrom<char> for Direction {
+    type Error = anyhow::Error;
+
+    fn try_from(c: char) -> Result<Self> {
+        match c {
+            'N' => Ok(Direction::North),
+            'W' => Ok(Direction::West),
+            'S' => Ok(Direction::South),
+            'E' => Ok(Direction::East),
+            other => bail!("Unknown direction: {other}"),
+        }
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let c = match self {
+            Direction::North => 'N',
+            Direction::West => 'W',
+            Direction::South => 'S',
+            Direction::East => 'E',
+        };
+        write!(f, "{c}")
+    }
+}
+
+// A platform of rounded (`O`) and cube-shaped (`#`) rocks. Both are kept as one bitset per row.
+// Cube rocks never move, so the stretches of free cells between them are computed once for every
+// row and every column. Tilting a line then only counts the rounded rocks of every stretch and
+// packs them to one of its ends, a few word operations each.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Platform {
+    width: usize,
+    height: usize,
+    rounded: Vec<Vec<u64>>,
+    cubes: Vec<Vec<u64>>,
+    row_segments: Vec<Vec<(usize, usize)>>,
+    column_segments: Vec<Vec<(usize, usize)>>,
+}
+
+impl Platform {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn is_rounded(&self, (row, column): Pos) -> bool {
+        self.rounded[row][column / WORD] & (1 << (column % WORD)) != 0
+    }
+
+    pub fn is_cube(&self, (row, column): Pos) -> bool {
+        self.cubes[row][column / WORD] & (1 << (column % WORD)) != 0
+    }
+
+    // number of rounded rocks
+    pub fn rounded_count(&self) -> usize {
+        self.rounded
+            .iter()
+            .flatten()
+            .map(|w| w.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn tilt(&mut self, direction: Direction) {
+        self.tilt_all(&[direction]);
+    }
+
+    // north, then west, then south, then east
+    pub fn cycle(&mut self) {
+        self.tilt_all(&Direction::CYCLE);
+    }
+
+    pub fn run(&mut self, program: &Program) {
+        for step in program.steps() {
+            match step {
+                Step::Tilts(directions) => self.tilt_all(directions),
+                Step::Repeat(body, times) => self.repeat(body, *times),
+            }
+        }
+    }
+
+    // Total load on the support beams along the given edge: every rounded rock weighs as much as
+    // its distance to the opposite edge, counted in cells including its own.
+    pub fn load(&self, edge: Direction) -> usize {
+        let (height, width) = (self.height, self.width);
+        match edge {
+            Direction::North | Direction::South => self
+                .rounded
+                .iter()
+                .enumerate()
+                .map(|(r, row)| {
+                    let weight = if edge == Direction::North {
+                        height - r
+                    } else {
+                        r + 1
+                    };
+                    weight * row.iter().map(|w| w.count_ones() as usize).sum::<usize>()
+                })
+                .sum(),
+            Direction::West | Direction::East => self
+                .rounded
+                .iter()
+                .flat_map(|row| ones(row))
+                .map(|c| {
+                    if edge == Direction::West {
+                        width - c
+                    } else {
+                        c + 1
+                    }
+                })
+                .sum(),
+        }
+    }
+
+    // Tilts in a row. The rocks are moved along columns while consecutive tilts allow it, so the
+    // rounded rocks are only transposed when the axis changes.
+    fn tilt_all(&mut self, directions: &[Direction]) {
+        let mut columns: Option<Vec<Vec<u64>>> = None;
+        for direction in directions {
+            match (direction.along_columns(), columns.as_mut()) {
+                (true, Some(lines)) => {
+                    tilt_lines(lines, &self.column_segments, direction.towards_start())
+                }
+                (true, None) => {
+                    let mut lines = transpose(&self.rounded, self.height, self.width);
+                    tilt_lines(&mut lines, &self.column_segments, direction.towards_start());
+                    columns = Some(lines);
+                }
+                (false, _) => {
+                    if let Some(lines) = columns.take() {
+                        self.rounded = transpose(&lines, self.width, self.height);
+                    }
+                    tilt_lines(
+                        &mut self.rounded,
+                        &self.row_segments,
+                        direction.towards_start(),
+                    );
+                }
+            }
+        }
+        if let Some(lines) = columns {
+            self.rounded = transpose(&lines, self.width, self.height);
+        }
+    }
+
+    // Runs the body `times` times. Platforms tend to settle into a loop quickly, so once a state
+    // comes back, the whole loops are skipped.
+    fn repeat(&mut self, body: &Program, times: u64) {
+        let orbit = self.explore(body, times, |_| ());
+        if let Some(period) = orbit.period {
+            for _ in 0..(times - orbit.runs) % period {
+                self.run(body);
+            }
+        }
+    }
+}
+
+impl FromStr for Platform {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let lines: Vec<&str> = s
+            .lines()
+            .map(|l| l.trim())
+            .filter(|l| !l.is_empty())
+            .collect();
+        let height = lines.len();
+        let width = lines.first().map_or(0, |l| l.len());
+        if width == 0 {
+            bail!("Platform is empty");
+        }
+
+        let mut rounded = vec![vec![0u64; width.div_ceil(WORD)]; height];
+        let mut cubes = vec![vec![0u64; width.div_ceil(WORD)]; height];
+        for (r, line) in lines.iter().enumerate() {
+            if line.len() != width {
+                bail!("Platform rows have different lengths");
+            }
+            for (c, symbol) in line.chars().enumerate() {
+                match symbol {
+                    'O' => rounded[r][c / WORD] |= 1 << (c % WORD),
+                    '#' => cubes[r][c / WORD] |= 1 << (c % WORD),
+                    '.' => (),
+                    other => bail!("Unknown platform symbol: {other}"),
+                }
+            }
+        }
+
+        let row_segments = cubes.iter().map(|row| segments(row, width)).collect();
+        let column_segments = transpose(&cubes, height, width)
+            .iter()
+            .map(|column| segments(column, height))
+            .collect();
+
+        Ok(Self {
+            width,
+            height,
+            rounded,
+            cubes,
+            row_segments,
+            column_segments,
+        })
+    }
+}
+
+impl fmt::Display for Platform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for r in 0..self.height {
+            let row: String = (0..self.width)
+                .map(|c| match (self.is_rounded((r, c)), self.is_cube((r, c))) {
+                    (true, _) => 'O',
+                    (_, true) => '#',
+                    _ => '.',
+                })
+                .collect();
+            writeln!(f, "{row}")?;
+        }
+        Ok(())
+    }
+}
+
+// the stretches `start..end` of free cells between the cubes of a line of length `len`
+fn segments(cubes: &[u64], len: usize) -> Vec<(usize, usize)> {
+    let mut segments = Vec::new();
+    let mut start = 0;
+    for cube in ones(cubes).chain([len]) {
+        if start < cube {
+            segments.push((start, cube));
+        }
+        start = cube + 1;
+    }
+    segments
+}
+
+// packs the rounded rocks of every segment to its start or to its end
+fn tilt_lines(lines: &mut [Vec<u64>], segments: &[Vec<(usize, usize)>], towards_start: bool) {
+    for (line, segments) in lines.iter_mut().zip(segments) {
+        for &(start, end) in segments {
+            let count: usize = masks(start, end)
+                .map(|(w, mask)| (line[w] & mask).count_ones() as usize)
+                .sum();
+            if count == 0 {
+                continue;
+            }
+            for (w, mask) in masks(start, end) {
+                line[w] &= !mask;
+            }
+            let (from, to) = if towards_start {
+                (start, start + count)
+            } else {
+                (end - count, end)
+            };
+            for (w, mask) in masks(from, to) {
+                line[w] |= mask;
+            }
+        }
+    }
+}
+
+// word index and bit mask of every word that the bit range `start..end` touches
+fn masks(start: usize, end: usize) -> impl Iterator<Item = (usize, u64)> {
+    (start / WORD..end.div_ceil(WORD)).map(move |w| {
+        let low = start.max(w * WORD) - w * WORD;
+        let high = end.min((w + 1) * WORD) - w * WORD;
+        let mask = if high - low == WORD {
+            !0
+        } else {
+            ((1 << (high - low)) - 1) << low
+        };
+        (w, mask)
+    })
+}
+
+// indices of the set bits
+fn ones(words: &[u64]) -> impl Iterator<Item = usize> + '_ {
+    words.iter().enumerate().flat_map(|(w, &word)| {
+        let mut bits = word;
+        std::iter::from_fn(move || {
+            (bits != 0).then(|| {
+                let bit = bits.trailing_zeros() as usize;
+                bits &= bits - 1;
+                w * WORD + bit
+            })
+        })
+    })
+}
+
+// Transposes a bit matrix of `height` lines of `width` bits each, one 64 by 64 block at a time.
+fn transpose(lines: &[Vec<u64>], height: usize, width: usize) -> Vec<Vec<u64>> {
+    let mut transposed = vec![vec![0u64; height.div_ceil(WORD)]; width];
+    let mut block = [0u64; WORD];
+    for br in 0..height.div_ceil(WORD) {
+        for bc in 0..width.div_ceil(WORD) {
+            for (i, word) in block.iter_mut().enumerate() {
+                *word = lines.get(br * WORD + i).map_or(0, |line| line[bc]);
+            }
+            transpose_block(&mut block);
+            for (j, &word) in block.iter().enumerate() {
+                if let Some(line) = transposed.get_mut(bc * WORD + j) {
+                    line[br] = word;
+                }
+            }
+        }
+    }
+    transposed
+}
+
+// In-place transpose of a 64 by 64 bit block, where bit `c` of word `r` is the cell (r, c). The
+// off-diagonal halves are swapped first, then the quarters within them and so on.
+fn transpose_block(block: &mut [u64; WORD]) {
+    let mut j = WORD / 2;
+    let mut mask: u64 = 0x0000_0000_FFFF_FFFF;
+    while j != 0 {
+        let mut k = 0;
+        while k < WORD {
+            let t = ((block[k] >> j) ^ block[k + j]) & mask;
+            block[k] ^= t << j;
+            block[k + j] ^= t;
+            k = (k + j + 1) & !j;
+        }
+        j >>= 1;
+        mask ^= mask << j;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    const EXAMPLE: &str = r#"
+        O....#....
+        O.OO#....#
+        .....##...
+        OO.#O....O
+        .O.....O#.
+        O.#..O.#.#
+        ..O..#O..O
+        .......O..
+        #....###..
+        #OO..#....
+    "#;
+
+    fn lines(s: &str) -> String {
+        s.lines()
+            .map(|l| l.trim())
+            .filter(|l| !l.is_empty())
+            .map(|l| format!("{l}\n"))
+            .collect()
+    }
+
+    // rocks as characters, rolled one cell per pass until none of them moves
+    fn naive_tilt(grid: &mut [Vec<u8>], direction: Direction) {
+        let (height, width) = (grid.len(), grid[0].len());
+        let (dr, dc): (isize, isize) = match direction {
+            Direction::North => (-1, 0),
+            Direction::West => (0, -1),
+            Direction::South => (1, 0),
+            Direction::East => (0, 1),
+        };
+        let mut moved = true;
+        while moved {
+            moved = false;
+            for r in 0..height {
+                for c in 0..width {
+                    let (nr, nc) = (r as isize + dr, c as isize + dc);
+                    if grid[r][c] != b'O'
+                        || !(0..height as isize).contains(&nr)
+                        || !(0..width as isize).contains(&nc)
+                        || grid[nr as usize][nc as usize] != b'.'
+                    {
+                        continue;
+                    }
+                    grid[nr as usize][nc as usize] = b'O';
+                    grid[r][c] = b'.';
+                    moved = true;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn tilts_and_cycles() {
+        let mut platform: Platform = EXAMPLE.parse().unwrap();
+        platform.tilt(Direction::North);
+        let tilted = r#"
+            OOOO.#.O..
+            OO..#....#
+            OO..O##..O
+            O..#.OO...
+            ........#.
+            ..#....#.#
+            ..O..#.O.O
+            ..O.......
+            #....###..
+            #....#....
+        "#;
+        assert_eq!(lines(tilted), platform.to_string());
+        assert_eq!(136, platform.load(Direction::North));
+
+        let mut platform: Platform = EXAMPLE.parse().unwrap();
+        platform.cycle();
+        let cycled = r#"
+            .....#....
+            ....#...O#
+            ...OO##...
+            .OO#......
+            .....OOO#.
+            .O#...O#.#
+            ....O#....
+            ......OOOO
+            #...O###..
+            #..OO#....
+        "#;
+        assert_eq!(lines(cycled), platform.to_string());
+
+        let mut platform: Platform = EXAMPLE.parse().unwrap();
+        platform.run(&"(NWSE)×1000000000".parse().unwrap());
+        assert_eq!(64, platform.load(Direction::North));
+        assert_eq!(18, platform.rounded_count());
+    }
+
+    #[test]
+    fn loads_under_every_edge() {
+        let platform: Platform = "O.#\n..O\n".parse().unwrap();
+        assert_eq!(2 + 1, platform.load(Direction::North));
+        assert_eq!(1 + 2, platform.load(Direction::South));
+        assert_eq!(3 + 1, platform.load(Direction::West));
+        assert_eq!(1 + 3, platform.load(Direction::East));
+        assert!(".#\n.x\n".parse::<Platform>().is_err());
+        assert!(".#\n.\n".parse::<Platform>().is_err());
+    }
+
+    #[test]
+    fn matches_naive_tilts_on_large_platforms() {
+        let (height, width) = (150, 130);
+        let mut rng = StdRng::seed_from_u64(14);
+        let mut grid: Vec<Vec<u8>> = (0..height)
+            .map(|_| {
+                (0..width)
+                    .map(|_| match rng.gen_range(0..10) {
+                        0 | 1 => b'#',
+                        2..=4 => b'O',
+                        _ => b'.',
+                    })
+                    .collect()
+            })
+            .collect();
+        let text: String = grid
+            .iter()
+            .map(|row| format!("{}\n", String::from_utf8_lossy(row)))
+            .collect();
+        let mut platform: Platform = text.parse().unwrap();
+        assert_eq!(text, platform.to_string());
+
+        let program: Program = "NNE S (WN)×3 E".parse().unwrap();
+        platform.run(&program);
+        for direction in "NNESWNWNWNE".chars() {
+            naive_tilt(&mut grid, direction.try_into().unwrap());
+        }
+        let text: String = grid
+            .iter()
+            .map(|row| format!("{}\n", String::from_utf8_lossy(row)))
+            .collect();
+        assert_eq!(text, platform.to_string());
+    }
+}
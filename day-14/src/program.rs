@@ -0,0 +1,180 @@
+use std::{fmt, iter::Peekable, str::Chars, str::FromStr};
+
+use anyhow::{bail, Result};
+
+use crate::Direction;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step {
+    // tilts one after another
+    Tilts(Vec<Direction>),
+    // the inner program, run the given number of times
+    Repeat(Program, u64),
+}
+
+// A sequence of tilts, written as direction letters. A run of letters or a parenthesised program
+// can be repeated with a `×` (or `x`, or `*`) suffix, which applies to the whole run:
+//   "NWSE"              one spin cycle
+//   "NNE×1000"          "NNE" a thousand times
+//   "N (WS)×3 E"        "NWSWSWSE"
+// Whitespace and commas between the parts are ignored.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Program {
+    steps: Vec<Step>,
+}
+
+impl Program {
+    pub fn new(steps: Vec<Step>) -> Self {
+        Self { steps }
+    }
+
+    // a single spin cycle: north, west, south, east
+    pub fn spin_cycle() -> Self {
+        Self::new(vec![Step::Tilts(Direction::CYCLE.to_vec())])
+    }
+
+    // this program, run `times` times
+    pub fn times(self, times: u64) -> Self {
+        Self::new(vec![Step::Repeat(self, times)])
+    }
+
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    // number of single tilts the program makes
+    pub fn tilt_count(&self) -> u128 {
+        self.steps
+            .iter()
+            .map(|step| match step {
+                Step::Tilts(directions) => directions.len() as u128,
+                Step::Repeat(body, times) => body.tilt_count() * *times as u128,
+            })
+            .sum()
+    }
+
+    fn parse(chars: &mut Peekable<Chars>, nested: bool) -> Result<Self> {
+        let mut steps = Vec::new();
+        loop {
+            let step = match chars.peek() {
+                None if nested => bail!("Missing closing parenthesis"),
+                None => break,
+                Some(')') if nested => {
+                    chars.next();
+                    break;
+                }
+                Some(c) if c.is_whitespace() || *c == ',' => {
+                    chars.next();
+                    continue;
+                }
+                Some('(') => {
+                    chars.next();
+                    Step::Repeat(Program::parse(chars, true)?, 1)
+                }
+                Some(_) => {
+                    let mut directions = Vec::new();
+                    while let Some(&c) = chars.peek() {
+                        if !"NWSE".contains(c) {
+                            break;
+                        }
+                        directions.push(Direction::try_from(c)?);
+                        chars.next();
+                    }
+                    if directions.is_empty() {
+                        bail!("Unexpected symbol: {}", chars.next().unwrap_or_default());
+                    }
+                    Step::Tilts(directions)
+                }
+            };
+
+            let step = match chars.peek() {
+                Some('×' | 'x' | '*') => {
+                    chars.next();
+                    let mut digits = String::new();
+                    while let Some(&c) = chars.peek().filter(|c| c.is_ascii_digit()) {
+                        digits.push(c);
+                        chars.next();
+                    }
+                    let Ok(times) = digits.parse() else {
+                        bail!("Missing repetition count");
+                    };
+                    match step {
+                        Step::Repeat(body, 1) => Step::Repeat(body, times),
+                        step => Step::Repeat(Program::new(vec![step]), times),
+                    }
+                }
+                _ => step,
+            };
+            steps.push(step);
+        }
+        Ok(Self::new(steps))
+    }
+}
+
+impl FromStr for Program {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Program::parse(&mut s.trim().chars().peekable(), false)
+    }
+}
+
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, step) in self.steps.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            match step {
+                Step::Tilts(directions) => {
+                    for direction in directions {
+                        write!(f, "{direction}")?;
+                    }
+                }
+                Step::Repeat(body, times) => write!(f, "({body})×{times}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_programs() {
+        let program: Program = "NNE×1000".parse().unwrap();
+        assert_eq!(
+            Program::new(vec![Step::Repeat(
+                Program::new(vec![Step::Tilts(vec![
+                    Direction::North,
+                    Direction::North,
+                    Direction::East
+                ])]),
+                1000
+            )]),
+            program
+        );
+        assert_eq!(3000, program.tilt_count());
+
+        let program: Program = "N, (WS)x3 E".parse().unwrap();
+        assert_eq!("N (WS)×3 E", program.to_string());
+        assert_eq!(8, program.tilt_count());
+        assert_eq!(program, program.to_string().parse().unwrap());
+
+        let program = Program::spin_cycle().times(1_000_000_000);
+        assert_eq!("(NWSE)×1000000000", program.to_string());
+        assert_eq!(program, "(NWSE)*1000000000".parse().unwrap());
+    }
+
+    #[test]
+    fn rejects_malformed_programs() {
+        assert!("NWQ".parse::<Program>().is_err());
+        assert!("(NW".parse::<Program>().is_err());
+        assert!("NW)".parse::<Program>().is_err());
+        assert!("NW×".parse::<Program>().is_err());
+        assert!("×3".parse::<Program>().is_err());
+        assert_eq!(0, "".parse::<Program>().unwrap().tilt_count());
+    }
+}
@@ -0,0 +1,189 @@
+use std::{collections::HashMap, fmt};
+
+use crate::{Direction, Platform, Program};
+
+// Where the states of a platform go when the same program is run over and over: `pre_period`
+// states lead into a loop of `period` states, which repeats forever. Without a period, no state
+// came back within the limit that was explored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Orbit {
+    pub(crate) pre_period: u64,
+    pub(crate) period: Option<u64>,
+    // number of runs made while exploring
+    pub(crate) runs: u64,
+}
+
+impl Platform {
+    // Runs the body until a state comes back or `limit` runs are made, and visits the initial
+    // state and every new state after it. On return the platform is in the last state reached.
+    pub(crate) fn explore(
+        &mut self,
+        body: &Program,
+        limit: u64,
+        mut visit: impl FnMut(&Platform),
+    ) -> Orbit {
+        let mut seen: HashMap<Vec<Vec<u64>>, u64> = HashMap::new();
+        let mut runs = 0;
+        loop {
+            if let Some(start) = seen.insert(self.rounded.clone(), runs) {
+                return Orbit {
+                    pre_period: start,
+                    period: Some(runs - start),
+                    runs,
+                };
+            }
+            visit(self);
+            if runs == limit {
+                return Orbit {
+                    pre_period: runs,
+                    period: None,
+                    runs,
+                };
+            }
+            self.run(body);
+            runs += 1;
+        }
+    }
+
+    // the loads after every run of the step, until the loads repeat or `limit` runs are made
+    pub fn timeline(&self, step: &Program, limit: u64) -> Timeline {
+        let mut loads = Vec::new();
+        let orbit = self.clone().explore(step, limit, |platform| {
+            loads.push(Direction::CYCLE.map(|edge| platform.load(edge)));
+        });
+        Timeline { loads, orbit }
+    }
+
+    // the timeline of spin cycles
+    pub fn cycle_timeline(&self, limit: u64) -> Timeline {
+        self.timeline(&Program::spin_cycle(), limit)
+    }
+}
+
+// The loads under every edge, after every run of a tilt program. The runs are only made until the
+// platform comes back to an earlier state, which answers the load after any number of runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Timeline {
+    // indexed by the number of runs, then by the edge in the order of `Direction::CYCLE`
+    loads: Vec<[usize; 4]>,
+    orbit: Orbit,
+}
+
+impl Timeline {
+    // number of runs before the loop, if one was found
+    pub fn pre_period(&self) -> Option<u64> {
+        self.orbit.period.map(|_| self.orbit.pre_period)
+    }
+
+    pub fn period(&self) -> Option<u64> {
+        self.orbit.period
+    }
+
+    // number of distinct states recorded, including the initial one
+    pub fn len(&self) -> usize {
+        self.loads.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.loads.is_empty()
+    }
+
+    // the load under the given edge after `runs` runs, known for any number of runs once a
+    // period is found, and only up to the limit otherwise
+    pub fn load_after(&self, runs: u64, edge: Direction) -> Option<usize> {
+        let index = match self.orbit.period {
+            Some(period) if runs >= self.orbit.pre_period => {
+                self.orbit.pre_period + (runs - self.orbit.pre_period) % period
+            }
+            _ => runs,
+        };
+        let edge = Direction::CYCLE.iter().position(|&d| d == edge)?;
+        self.loads
+            .get(usize::try_from(index).ok()?)
+            .map(|l| l[edge])
+    }
+
+    // the loads after 0, 1, ..., `limit` runs, as far as they are known
+    pub fn loads(&self, edge: Direction, limit: u64) -> Vec<usize> {
+        (0..=limit)
+            .map_while(|runs| self.load_after(runs, edge))
+            .collect()
+    }
+}
+
+impl fmt::Display for Timeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.orbit.period {
+            Some(period) => write!(
+                f,
+                "{} runs before a loop of {period} runs",
+                self.orbit.pre_period
+            ),
+            None => write!(f, "no loop within {} runs", self.orbit.runs),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = r#"
+        O....#....
+        O.OO#....#
+        .....##...
+        OO.#O....O
+        .O.....O#.
+        O.#..O.#.#
+        ..O..#O..O
+        .......O..
+        #....###..
+        #OO..#....
+    "#;
+
+    #[test]
+    fn spin_cycle_timeline() {
+        let platform: Platform = EXAMPLE.parse().unwrap();
+        let timeline = platform.cycle_timeline(u64::MAX);
+        assert_eq!(Some(3), timeline.pre_period());
+        assert_eq!(Some(7), timeline.period());
+        assert_eq!(10, timeline.len());
+        assert_eq!("3 runs before a loop of 7 runs", timeline.to_string());
+
+        assert_eq!(Some(104), timeline.load_after(0, Direction::North));
+        assert_eq!(
+            Some(64),
+            timeline.load_after(1_000_000_000, Direction::North)
+        );
+
+        // every answer matches actually spinning the platform
+        let mut spun = platform.clone();
+        for runs in 0..40 {
+            for edge in Direction::CYCLE {
+                assert_eq!(Some(spun.load(edge)), timeline.load_after(runs, edge));
+            }
+            spun.cycle();
+        }
+        assert_eq!(41, timeline.loads(Direction::West, 40).len());
+    }
+
+    #[test]
+    fn limited_timeline() {
+        let platform: Platform = EXAMPLE.parse().unwrap();
+        let timeline = platform.cycle_timeline(2);
+        assert_eq!(None, timeline.period());
+        assert_eq!(None, timeline.pre_period());
+        assert_eq!("no loop within 2 runs", timeline.to_string());
+        assert_eq!(3, timeline.loads(Direction::North, 100).len());
+        assert_eq!(None, timeline.load_after(3, Direction::North));
+
+        // tilting north twice settles right away
+        let timeline = platform.timeline(&"N".parse().unwrap(), 100);
+        assert_eq!(Some(1), timeline.pre_period());
+        assert_eq!(Some(1), timeline.period());
+        assert_eq!(
+            vec![104, 136, 136, 136],
+            timeline.loads(Direction::North, 3)
+        );
+    }
+}
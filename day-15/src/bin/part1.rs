@@ -1,3 +1,5 @@
+use day_15::find_hash;
+
 fn main() {
     let input = include_str!("./input.txt");
     let answer = process(input);
@@ -20,12 +22,6 @@ fn process_line(l: &str) -> usize {
     l.split(',').fold(0, |acc, s| acc + find_hash(s))
 }
 
-fn find_hash(s: &str) -> usize {
-    s.as_bytes()
-        .iter()
-        .fold(0, |acc, b| ((acc + (*b as usize)) * 17) % 256)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,457 @@
+use std::{
+    fmt,
+    io::{self, BufRead},
+};
+
+use crate::{Hasher, LensMap};
+
+// how many steps apart the interpreter keeps copies of the boxes, to rewind quickly
+const CHECKPOINT: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    // `-`
+    Remove,
+    // `=` and the focal length
+    Insert(usize),
+}
+
+// a single step of the initialization sequence, found `offset` bytes into the input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub label: String,
+    pub operation: Operation,
+    pub offset: usize,
+}
+
+impl Step {
+    pub fn parse(text: &[u8], offset: usize) -> Result<Self, StepError> {
+        let error = |kind| StepError {
+            offset,
+            text: String::from_utf8_lossy(text).into_owned(),
+            kind,
+        };
+        if text.is_empty() {
+            return Err(error(StepErrorKind::Empty));
+        }
+        let Some(split) = text.iter().position(|&b| b == b'=' || b == b'-') else {
+            return Err(error(StepErrorKind::MissingOperation));
+        };
+        let (label, rest) = text.split_at(split);
+        if label.is_empty() || !label.iter().all(u8::is_ascii_alphanumeric) {
+            return Err(error(StepErrorKind::InvalidLabel));
+        }
+
+        let operation = match rest {
+            [b'-'] => Operation::Remove,
+            [b'-', ..] => return Err(error(StepErrorKind::TrailingText)),
+            [b'=', focal_length @ ..] => match focal_length {
+                [digit @ b'1'..=b'9'] => Operation::Insert((digit - b'0') as usize),
+                _ => return Err(error(StepErrorKind::InvalidFocalLength)),
+            },
+            _ => unreachable!("the operation starts with `=` or `-`"),
+        };
+        Ok(Self {
+            label: String::from_utf8_lossy(label).into_owned(),
+            operation,
+            offset,
+        })
+    }
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.operation {
+            Operation::Remove => write!(f, "{}-", self.label),
+            Operation::Insert(focal_length) => write!(f, "{}={focal_length}", self.label),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepErrorKind {
+    // nothing between two commas
+    Empty,
+    // neither `=` nor `-`
+    MissingOperation,
+    // the label is empty or has something other than letters and digits
+    InvalidLabel,
+    // the focal length is not a single digit from 1 to 9
+    InvalidFocalLength,
+    // something after `-`
+    TrailingText,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepError {
+    pub offset: usize,
+    pub text: String,
+    pub kind: StepErrorKind,
+}
+
+impl fmt::Display for StepError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let problem = match self.kind {
+            StepErrorKind::Empty => "empty step",
+            StepErrorKind::MissingOperation => "missing `=` or `-`",
+            StepErrorKind::InvalidLabel => "label must be letters and digits",
+            StepErrorKind::InvalidFocalLength => "focal length must be a digit from 1 to 9",
+            StepErrorKind::TrailingText => "unexpected text after `-`",
+        };
+        write!(
+            f,
+            "Invalid step {:?} at offset {}: {problem}",
+            self.text, self.offset
+        )
+    }
+}
+
+impl std::error::Error for StepError {}
+
+#[derive(Debug)]
+pub enum ReadError {
+    Io(io::Error),
+    Step(StepError),
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadError::Io(error) => write!(f, "Failed to read the sequence: {error}"),
+            ReadError::Step(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for ReadError {}
+
+// Reads the comma separated steps one at a time, without holding the whole input. Whitespace
+// around a step, including line breaks, is ignored.
+pub struct Steps<R> {
+    reader: R,
+    offset: usize,
+    buf: Vec<u8>,
+}
+
+pub fn read_steps<R: BufRead>(reader: R) -> Steps<R> {
+    Steps {
+        reader,
+        offset: 0,
+        buf: Vec::new(),
+    }
+}
+
+impl<R: BufRead> Iterator for Steps<R> {
+    type Item = Result<Step, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.buf.clear();
+        let read = match self.reader.read_until(b',', &mut self.buf) {
+            Ok(0) => return None,
+            Ok(read) => read,
+            Err(error) => return Some(Err(ReadError::Io(error))),
+        };
+        let start = self.offset;
+        self.offset += read;
+
+        let separated = self.buf.last() == Some(&b',');
+        let text = self.buf.strip_suffix(b",").unwrap_or(&self.buf);
+        let leading = text.iter().take_while(|b| b.is_ascii_whitespace()).count();
+        let text = text[leading..].trim_ascii_end();
+        // whitespace after the last step
+        if text.is_empty() && !separated {
+            return None;
+        }
+        Some(Step::parse(text, start + leading).map_err(ReadError::Step))
+    }
+}
+
+// All valid steps of a sequence, and the problems with the other ones.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InitSequence {
+    pub steps: Vec<Step>,
+    pub errors: Vec<StepError>,
+}
+
+impl InitSequence {
+    pub fn read<R: BufRead>(reader: R) -> io::Result<Self> {
+        let mut sequence = Self::default();
+        for step in read_steps(reader) {
+            match step {
+                Ok(step) => sequence.steps.push(step),
+                Err(ReadError::Step(error)) => sequence.errors.push(error),
+                Err(ReadError::Io(error)) => return Err(error),
+            }
+        }
+        Ok(sequence)
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    // the steps, as long as all of them are valid
+    pub fn validated(self) -> Result<Vec<Step>, StepError> {
+        match self.errors.into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(self.steps),
+        }
+    }
+}
+
+// What the boxes look like after a step: every box that holds lenses, with the lenses in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogEntry {
+    pub index: usize,
+    pub step: Step,
+    pub boxes: Vec<(usize, Vec<(String, usize)>)>,
+}
+
+impl fmt::Display for LogEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "After \"{}\":", self.step)?;
+        for (index, lenses) in &self.boxes {
+            write!(f, "Box {index}:")?;
+            for (label, focal_length) in lenses {
+                write!(f, " [{label} {focal_length}]")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+// Runs the initialization sequence step by step. It can move to any step, forwards by running
+// the steps in between, backwards by going back to the last copy of the boxes before that step.
+#[derive(Debug, Clone)]
+pub struct Interpreter {
+    steps: Vec<Step>,
+    boxes: LensMap<String, usize>,
+    position: usize,
+    // the boxes before step 0, CHECKPOINT, 2 * CHECKPOINT, ...
+    checkpoints: Vec<LensMap<String, usize>>,
+}
+
+impl Interpreter {
+    pub fn new(steps: Vec<Step>) -> Self {
+        Self {
+            steps,
+            boxes: LensMap::new(),
+            position: 0,
+            checkpoints: Vec::new(),
+        }
+    }
+
+    // the same steps with the lenses spread by a different hasher, back at the first step
+    pub fn with_hasher(self, hasher: Hasher) -> Self {
+        Self {
+            boxes: LensMap::with_hasher(hasher),
+            position: 0,
+            checkpoints: Vec::new(),
+            ..self
+        }
+    }
+
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    // number of steps done so far
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position == self.steps.len()
+    }
+
+    pub fn boxes(&self) -> &LensMap<String, usize> {
+        &self.boxes
+    }
+
+    pub fn focusing_power(&self) -> usize {
+        self.boxes.focusing_power()
+    }
+
+    // Does the next step and logs the boxes after it.
+    pub fn step(&mut self) -> Option<LogEntry> {
+        self.advance()?;
+        let index = self.position - 1;
+        let boxes = self
+            .boxes
+            .buckets()
+            .enumerate()
+            .filter(|(_, lenses)| !lenses.is_empty())
+            .map(|(b, lenses)| (b, lenses.to_vec()))
+            .collect();
+        Some(LogEntry {
+            index,
+            step: self.steps[index].clone(),
+            boxes,
+        })
+    }
+
+    // does all the remaining steps
+    pub fn run(&mut self) {
+        while self.advance().is_some() {}
+    }
+
+    // Moves to the state after the first `position` steps, forwards or backwards. Positions past
+    // the last step stop at the end of the sequence.
+    pub fn seek(&mut self, position: usize) {
+        let position = position.min(self.steps.len());
+        if position < self.position {
+            let checkpoint = position / CHECKPOINT;
+            self.boxes = self.checkpoints[checkpoint].clone();
+            self.position = checkpoint * CHECKPOINT;
+        }
+        while self.position < position {
+            self.advance();
+        }
+    }
+
+    // the log of the whole sequence, from the first step, leaving this interpreter as it is
+    pub fn log(&self) -> impl Iterator<Item = LogEntry> {
+        let mut replay = Interpreter::new(self.steps.clone()).with_hasher(self.boxes.hasher());
+        std::iter::from_fn(move || replay.step())
+    }
+
+    fn advance(&mut self) -> Option<()> {
+        let step = self.steps.get(self.position)?;
+        if self.position.is_multiple_of(CHECKPOINT)
+            && self.checkpoints.len() == self.position / CHECKPOINT
+        {
+            self.checkpoints.push(self.boxes.clone());
+        }
+        match step.operation {
+            Operation::Remove => {
+                self.boxes.remove(step.label.as_str());
+            }
+            Operation::Insert(focal_length) => {
+                self.boxes.insert(step.label.clone(), focal_length);
+            }
+        }
+        self.position += 1;
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{io::BufReader, num::NonZeroUsize};
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    const EXAMPLE: &str = "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7";
+
+    #[test]
+    fn manual_log() {
+        let steps = InitSequence::read(EXAMPLE.as_bytes())
+            .unwrap()
+            .validated()
+            .unwrap();
+        let interpreter = Interpreter::new(steps);
+        let log: Vec<LogEntry> = interpreter.log().collect();
+        assert_eq!(11, log.len());
+        assert_eq!("After \"rn=1\":\nBox 0: [rn 1]\n", log[0].to_string());
+        assert_eq!(
+            "After \"ot=7\":\nBox 0: [rn 1] [cm 2]\nBox 3: [ot 7] [ab 5] [pc 6]\n",
+            log[10].to_string()
+        );
+        assert_eq!(47, log[10].step.offset);
+        assert_eq!(0, interpreter.position());
+    }
+
+    #[test]
+    fn replay_and_rewind() {
+        // a long sequence with plenty of replaced and removed lenses
+        let mut rng = StdRng::seed_from_u64(15);
+        let text: Vec<String> = (0..500)
+            .map(|_| {
+                let label = ["rn", "cm", "qp", "pc", "ot", "ab", "x", "yz"][rng.gen_range(0..8)];
+                match rng.gen_range(0..10) {
+                    0 => format!("{label}-"),
+                    focal_length => format!("{label}={focal_length}"),
+                }
+            })
+            .collect();
+        let steps = InitSequence::read(text.join(",").as_bytes())
+            .unwrap()
+            .validated()
+            .unwrap();
+
+        let mut interpreter = Interpreter::new(steps.clone());
+        let powers: Vec<usize> = std::iter::once(0)
+            .chain(interpreter.log().map(|entry| {
+                entry
+                    .boxes
+                    .iter()
+                    .flat_map(|(b, lenses)| {
+                        lenses
+                            .iter()
+                            .enumerate()
+                            .map(move |(s, (_, f))| (b + 1) * (s + 1) * f)
+                    })
+                    .sum()
+            }))
+            .collect();
+        for position in [500, 3, 499, 0, 64, 63, 65, 250, 128, 500, 1] {
+            interpreter.seek(position);
+            assert_eq!(position, interpreter.position());
+            assert_eq!(powers[position], interpreter.focusing_power());
+        }
+        interpreter.seek(501);
+        assert_eq!(500, interpreter.position());
+        interpreter.seek(0);
+        interpreter.run();
+        assert!(interpreter.is_finished());
+
+        // the same steps spread over fewer boxes
+        let mut interpreter =
+            interpreter.with_hasher(Hasher::new(3, NonZeroUsize::new(8).unwrap()));
+        assert_eq!(0, interpreter.position());
+        interpreter.run();
+        assert_eq!(8, interpreter.boxes().buckets().count());
+    }
+
+    #[test]
+    fn invalid_steps_and_streaming() {
+        let input = "  rn=1,cm-,,qp=x,\nab,c!=3,pc=10,ot-5,=2,\nab=5\n";
+        // a tiny buffer, so that steps are read in pieces
+        let sequence = InitSequence::read(BufReader::with_capacity(3, input.as_bytes())).unwrap();
+        assert!(!sequence.is_valid());
+        assert_eq!(
+            vec!["rn=1", "cm-", "ab=5"],
+            sequence
+                .steps
+                .iter()
+                .map(|s| s.to_string())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![
+                (11, StepErrorKind::Empty),
+                (12, StepErrorKind::InvalidFocalLength),
+                (18, StepErrorKind::MissingOperation),
+                (21, StepErrorKind::InvalidLabel),
+                (26, StepErrorKind::InvalidFocalLength),
+                (32, StepErrorKind::TrailingText),
+                (37, StepErrorKind::InvalidLabel),
+            ],
+            sequence
+                .errors
+                .iter()
+                .map(|e| (e.offset, e.kind))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(2, sequence.steps[0].offset);
+        assert_eq!(41, sequence.steps[2].offset);
+        assert_eq!(
+            "Invalid step \"qp=x\" at offset 12: focal length must be a digit from 1 to 9",
+            sequence.errors[1].to_string()
+        );
+        assert_eq!(Err(sequence.errors[0].clone()), sequence.validated());
+    }
+}
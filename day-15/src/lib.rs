@@ -0,0 +1,332 @@
+use std::{borrow::Borrow, num::NonZeroUsize};
+
+pub mod interpreter;
+
+// The HASH algorithm: starting from the seed, add the code of every byte, multiply by 17 and keep
+// the remainder modulo the number of buckets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hasher {
+    seed: usize,
+    buckets: usize,
+}
+
+impl Default for Hasher {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            buckets: 256,
+        }
+    }
+}
+
+impl Hasher {
+    pub fn new(seed: usize, buckets: NonZeroUsize) -> Self {
+        Self {
+            seed,
+            buckets: buckets.get(),
+        }
+    }
+
+    pub fn seed(&self) -> usize {
+        self.seed
+    }
+
+    pub fn buckets(&self) -> usize {
+        self.buckets
+    }
+
+    // the steps are computed in u128, so that any bucket count works without overflowing
+    pub fn hash(&self, bytes: &[u8]) -> usize {
+        let buckets = self.buckets as u128;
+        bytes.iter().fold(self.seed % self.buckets, |acc, &b| {
+            ((acc as u128 + b as u128) * 17 % buckets) as usize
+        })
+    }
+}
+
+// HASH with the puzzle's parameters
+pub fn find_hash(s: &str) -> usize {
+    Hasher::default().hash(s.as_bytes())
+}
+
+// The HASHMAP: keys are spread over buckets by HASH, and every bucket keeps its entries in the
+// order they were first inserted. Replacing the value of a key keeps its place, removing a key
+// moves the entries behind it one slot forward.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LensMap<K, V> {
+    hasher: Hasher,
+    buckets: Vec<Vec<(K, V)>>,
+    len: usize,
+}
+
+impl<K, V> Default for LensMap<K, V> {
+    fn default() -> Self {
+        Self::with_hasher(Hasher::default())
+    }
+}
+
+impl<K, V> LensMap<K, V> {
+    // 256 buckets and seed 0, as in the manual
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_hasher(hasher: Hasher) -> Self {
+        Self {
+            hasher,
+            buckets: (0..hasher.buckets()).map(|_| Vec::new()).collect(),
+            len: 0,
+        }
+    }
+
+    // the same map with its entries spread over a different number of buckets
+    pub fn with_bucket_count(self, buckets: NonZeroUsize) -> Self
+    where
+        K: AsRef<[u8]>,
+    {
+        let hasher = Hasher::new(self.hasher.seed(), buckets);
+        self.rehash(hasher)
+    }
+
+    // the same map with its entries spread by a different seed
+    pub fn with_seed(self, seed: usize) -> Self
+    where
+        K: AsRef<[u8]>,
+    {
+        let hasher = Hasher {
+            seed,
+            ..self.hasher
+        };
+        self.rehash(hasher)
+    }
+
+    pub fn hasher(&self) -> Hasher {
+        self.hasher
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // the entries of a single bucket, in order
+    pub fn bucket(&self, index: usize) -> &[(K, V)] {
+        &self.buckets[index]
+    }
+
+    // every bucket, including the empty ones
+    pub fn buckets(&self) -> impl Iterator<Item = &[(K, V)]> {
+        self.buckets.iter().map(|bucket| bucket.as_slice())
+    }
+
+    // all entries, bucket by bucket
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.buckets.iter().flatten().map(|(k, v)| (k, v))
+    }
+
+    // all entries together with their bucket and their slot within it
+    pub fn slots(&self) -> impl Iterator<Item = (usize, usize, &K, &V)> {
+        self.buckets.iter().enumerate().flat_map(|(b, bucket)| {
+            bucket
+                .iter()
+                .enumerate()
+                .map(move |(s, (k, v))| (b, s, k, v))
+        })
+    }
+
+    pub fn clear(&mut self) {
+        self.buckets.iter_mut().for_each(Vec::clear);
+        self.len = 0;
+    }
+
+    // Sum of bucket number times slot number times value, both numbers counted from 1.
+    pub fn focusing_power(&self) -> usize
+    where
+        V: Copy + Into<usize>,
+    {
+        self.slots()
+            .map(|(b, s, _, &v)| (b + 1) * (s + 1) * v.into())
+            .sum()
+    }
+
+    fn rehash(self, hasher: Hasher) -> Self
+    where
+        K: AsRef<[u8]>,
+    {
+        let mut map = Self::with_hasher(hasher);
+        for (k, v) in self.buckets.into_iter().flatten() {
+            map.buckets[hasher.hash(k.as_ref())].push((k, v));
+        }
+        map.len = self.len;
+        map
+    }
+}
+
+impl<K: AsRef<[u8]> + Eq, V> LensMap<K, V> {
+    // bucket the key belongs to
+    pub fn bucket_of<Q>(&self, key: &Q) -> usize
+    where
+        Q: AsRef<[u8]> + ?Sized,
+    {
+        self.hasher.hash(key.as_ref())
+    }
+
+    // Inserts or replaces, returning the value that was replaced.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let bucket = &mut self.buckets[self.hasher.hash(key.as_ref())];
+        match bucket.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, old)) => Some(std::mem::replace(old, value)),
+            None => {
+                bucket.push((key, value));
+                self.len += 1;
+                None
+            }
+        }
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: AsRef<[u8]> + Eq + ?Sized,
+    {
+        let bucket = &mut self.buckets[self.hasher.hash(key.as_ref())];
+        let slot = bucket.iter().position(|(k, _)| k.borrow() == key)?;
+        self.len -= 1;
+        Some(bucket.remove(slot).1)
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: AsRef<[u8]> + Eq + ?Sized,
+    {
+        self.buckets[self.hasher.hash(key.as_ref())]
+            .iter()
+            .find(|(k, _)| k.borrow() == key)
+            .map(|(_, v)| v)
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: AsRef<[u8]> + Eq + ?Sized,
+    {
+        self.buckets[self.hasher.hash(key.as_ref())]
+            .iter_mut()
+            .find(|(k, _)| k.borrow() == key)
+            .map(|(_, v)| v)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: AsRef<[u8]> + Eq + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    // bucket and slot of the key
+    pub fn position<Q>(&self, key: &Q) -> Option<(usize, usize)>
+    where
+        K: Borrow<Q>,
+        Q: AsRef<[u8]> + Eq + ?Sized,
+    {
+        let bucket = self.hasher.hash(key.as_ref());
+        let slot = self.buckets[bucket]
+            .iter()
+            .position(|(k, _)| k.borrow() == key)?;
+        Some((bucket, slot))
+    }
+}
+
+impl<K: AsRef<[u8]> + Eq, V> Extend<(K, V)> for LensMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<K: AsRef<[u8]> + Eq, V> FromIterator<(K, V)> for LensMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashes() {
+        assert_eq!(52, find_hash("HASH"));
+        assert_eq!(0, find_hash("rn"));
+        assert_eq!(3, find_hash("pc"));
+        let buckets = |n| NonZeroUsize::new(n).unwrap();
+        assert_eq!(52 % 16, Hasher::new(0, buckets(16)).hash(b"HASH"));
+        // a seed is the value before the first byte
+        assert_eq!(
+            find_hash("HASH"),
+            Hasher::new(find_hash("HA"), buckets(256)).hash(b"SH")
+        );
+        // (usize::MAX + 1) * 17 is far beyond usize
+        let wide = Hasher::new(usize::MAX - 1, buckets(usize::MAX));
+        assert_eq!(17, wide.hash(&[2]));
+        assert_eq!(17 * 18, wide.hash(&[2, 1]));
+    }
+
+    #[test]
+    fn manual_example() {
+        let mut map: LensMap<&str, usize> = LensMap::new();
+        map.insert("rn", 1);
+        map.remove("cm");
+        map.insert("qp", 3);
+        map.insert("cm", 2);
+        assert_eq!(Some(3), map.remove("qp"));
+        map.insert("pc", 4);
+        map.insert("ot", 9);
+        map.insert("ab", 5);
+        map.remove("pc");
+        map.insert("pc", 6);
+        assert_eq!(Some(9), map.insert("ot", 7));
+
+        assert_eq!(5, map.len());
+        assert_eq!(&[("rn", 1), ("cm", 2)], map.bucket(0));
+        assert_eq!(&[("ot", 7), ("ab", 5), ("pc", 6)], map.bucket(3));
+        assert_eq!(Some((3, 1)), map.position("ab"));
+        assert_eq!(Some(&7), map.get("ot"));
+        assert!(!map.contains_key("qp"));
+        assert_eq!(145, map.focusing_power());
+    }
+
+    #[test]
+    fn buckets_and_seeds() {
+        let map: LensMap<String, u8> = ["rn", "cm", "ot", "ab", "pc"]
+            .into_iter()
+            .zip(1..)
+            .map(|(k, v)| (k.to_string(), v))
+            .collect();
+        let buckets = NonZeroUsize::new(4).unwrap();
+        let map = map.with_bucket_count(buckets).with_seed(7);
+        assert_eq!(Hasher::new(7, buckets), map.hasher());
+        assert_eq!(4, map.buckets().count());
+        assert_eq!(5, map.len());
+        for (b, s, k, v) in map.slots() {
+            assert_eq!(b, map.bucket_of(k));
+            assert_eq!(Some((b, s)), map.position(k.as_str()));
+            assert_eq!(Some(v), map.get(k.as_str()));
+        }
+
+        let mut map = map;
+        *map.get_mut("ab").unwrap() = 40;
+        assert_eq!(Some(40), map.remove("ab"));
+        map.clear();
+        assert!(map.is_empty());
+        assert_eq!(0, map.focusing_power());
+    }
+}
@@ -0,0 +1,522 @@
+use crate::{Contraption, Direction, Node, Pos};
+
+const WORD: usize = u64::BITS as usize;
+
+// A set of tiles of a contraption, one bit per tile.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TileSet {
+    width: usize,
+    bits: Vec<u64>,
+}
+
+impl TileSet {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            bits: vec![0; (width * height).div_ceil(WORD)],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.bits.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits.iter().all(|&w| w == 0)
+    }
+
+    pub fn contains(&self, (row, col): Pos) -> bool {
+        let tile = row * self.width + col;
+        self.bits[tile / WORD] & (1 << (tile % WORD)) != 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Pos> + '_ {
+        self.bits.iter().enumerate().flat_map(move |(w, &word)| {
+            (0..WORD)
+                .filter(move |bit| word & (1 << bit) != 0)
+                .map(move |bit| {
+                    let tile = w * WORD + bit;
+                    (tile / self.width, tile % self.width)
+                })
+        })
+    }
+
+    fn insert(&mut self, tile: usize) {
+        self.bits[tile / WORD] |= 1 << (tile % WORD);
+    }
+
+    fn union_with(&mut self, other: &TileSet) {
+        for (a, b) in self.bits.iter_mut().zip(&other.bits) {
+            *a |= b;
+        }
+    }
+}
+
+// A strongly connected component of beam states: every state in it reaches every other one, so
+// they all energize the same tiles.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Component {
+    // distinct tiles of the states
+    tiles: Vec<usize>,
+    // distinct components right after this one, all with smaller numbers
+    successors: Vec<usize>,
+    size: usize,
+}
+
+// The directed graph of beam states, a tile together with the direction the beam moves in, with
+// an edge to every state the beam continues in. It is condensed into its strongly connected
+// components, numbered so that every edge of the condensation goes to a smaller number.
+#[derive(Debug, Clone)]
+pub struct BeamGraph {
+    width: usize,
+    height: usize,
+    // component of every state, states are numbered `4 * tile + direction`
+    component: Vec<usize>,
+    components: Vec<Component>,
+}
+
+impl BeamGraph {
+    pub fn new(contraption: &Contraption) -> Self {
+        let (width, height) = (contraption.width(), contraption.height());
+        let state = |((row, col), dir): Node| 4 * (row * width + col) + dir.index();
+        let mut edges = Vec::with_capacity(4 * width * height);
+        for row in 0..height {
+            for col in 0..width {
+                for dir in Direction::ALL {
+                    let next = contraption.next_nodes(((row, col), dir));
+                    edges.push(next.into_iter().map(state).collect::<Vec<usize>>());
+                }
+            }
+        }
+
+        let (component, count) = strongly_connected(&edges);
+        let mut components = vec![
+            Component {
+                tiles: Vec::new(),
+                successors: Vec::new(),
+                size: 0,
+            };
+            count
+        ];
+        for (s, targets) in edges.iter().enumerate() {
+            let c = &mut components[component[s]];
+            c.size += 1;
+            if c.tiles.last() != Some(&(s / 4)) {
+                c.tiles.push(s / 4);
+            }
+            c.successors.extend(
+                targets
+                    .iter()
+                    .map(|&t| component[t])
+                    .filter(|&t| t != component[s]),
+            );
+        }
+        for c in components.iter_mut() {
+            c.tiles.sort_unstable();
+            c.tiles.dedup();
+            c.successors.sort_unstable();
+            c.successors.dedup();
+        }
+
+        Self {
+            width,
+            height,
+            component,
+            components,
+        }
+    }
+
+    pub fn state_count(&self) -> usize {
+        self.component.len()
+    }
+
+    pub fn component_count(&self) -> usize {
+        self.components.len()
+    }
+
+    pub fn component_of(&self, ((row, col), dir): Node) -> usize {
+        self.component[4 * (row * self.width + col) + dir.index()]
+    }
+
+    // number of states in the component
+    pub fn component_size(&self, component: usize) -> usize {
+        self.components[component].size
+    }
+
+    // the components right after the given one
+    pub fn successors(&self, component: usize) -> &[usize] {
+        &self.components[component].successors
+    }
+
+    // the tiles energized by a single beam entering at the given state
+    pub fn energized(&self, entry: Node) -> TileSet {
+        let mut seen = vec![0; self.components.len()];
+        let shared = vec![false; self.components.len()];
+        let (walked, _) = self.walk(self.component_of(entry), &shared, &mut seen, 1);
+        self.collect(&walked, &[], &[])
+    }
+
+    // Energized tiles for every entry point of the contraption. The tiles below components that
+    // several others lead to, or that hold a loop of states, are collected once and reused by
+    // every walk reaching them. Since every other component has at most one predecessor, walks
+    // between the shared ones never overlap.
+    //
+    // Only components that some entry point reaches are shared, and components are done in
+    // increasing order, entry points right after their own component. Every shared set counts
+    // down the walks still to use it and is dropped after the last one.
+    pub fn energized_from_entries(&self, contraption: &Contraption) -> Vec<(Node, TileSet)> {
+        let entries = contraption.entry_points();
+        let roots: Vec<usize> = entries.iter().map(|&e| self.component_of(e)).collect();
+        let shared = self.shared_components(&roots);
+
+        let mut seen = vec![0; self.components.len()];
+        let mut stamp = 0;
+        let mut uses = vec![0usize; self.components.len()];
+        let walk_roots = (0..self.components.len())
+            .filter(|&c| shared[c])
+            .chain(roots.iter().copied().filter(|&r| !shared[r]));
+        for root in walk_roots {
+            stamp += 1;
+            for s in self.walk(root, &shared, &mut seen, stamp).1 {
+                uses[s] += 1;
+            }
+        }
+        for &r in roots.iter().filter(|&&r| shared[r]) {
+            uses[r] += 1;
+        }
+
+        let mut order: Vec<usize> = (0..entries.len()).collect();
+        order.sort_by_key(|&i| roots[i]);
+        let mut order = order.into_iter().peekable();
+        let mut sets: Vec<Option<TileSet>> = vec![None; self.components.len()];
+        let mut energized: Vec<Option<TileSet>> = vec![None; entries.len()];
+        for c in 0..self.components.len() {
+            if shared[c] {
+                stamp += 1;
+                let (walked, stops) = self.walk(c, &shared, &mut seen, stamp);
+                sets[c] = Some(self.collect(&walked, &stops, &sets));
+                release(&stops, &mut uses, &mut sets);
+            }
+            while let Some(i) = order.next_if(|&i| roots[i] == c) {
+                let tiles = if shared[c] {
+                    uses[c] -= 1;
+                    if uses[c] == 0 {
+                        sets[c].take()
+                    } else {
+                        sets[c].clone()
+                    }
+                } else {
+                    stamp += 1;
+                    let (walked, stops) = self.walk(c, &shared, &mut seen, stamp);
+                    let tiles = self.collect(&walked, &stops, &sets);
+                    release(&stops, &mut uses, &mut sets);
+                    Some(tiles)
+                };
+                energized[i] = tiles;
+            }
+        }
+
+        entries
+            .into_iter()
+            .zip(energized)
+            .filter_map(|(entry, tiles)| Some((entry, tiles?)))
+            .collect()
+    }
+
+    // The components whose tiles are worth collecting once: reachable from one of the roots, and
+    // either reached from several reachable components or holding a loop of states.
+    fn shared_components(&self, roots: &[usize]) -> Vec<bool> {
+        let mut reachable = vec![false; self.components.len()];
+        let mut stack: Vec<usize> = roots.to_vec();
+        for &r in roots {
+            reachable[r] = true;
+        }
+        while let Some(c) = stack.pop() {
+            for &s in &self.components[c].successors {
+                if !reachable[s] {
+                    reachable[s] = true;
+                    stack.push(s);
+                }
+            }
+        }
+
+        let mut predecessors = vec![0usize; self.components.len()];
+        for (_, c) in self
+            .components
+            .iter()
+            .enumerate()
+            .filter(|&(c, _)| reachable[c])
+        {
+            for &s in &c.successors {
+                predecessors[s] += 1;
+            }
+        }
+        (0..self.components.len())
+            .map(|c| reachable[c] && (predecessors[c] > 1 || self.components[c].size > 1))
+            .collect()
+    }
+
+    // All components reachable from `root` without passing a shared one, and the shared ones the
+    // walk stops at.
+    fn walk(
+        &self,
+        root: usize,
+        shared: &[bool],
+        seen: &mut [u32],
+        stamp: u32,
+    ) -> (Vec<usize>, Vec<usize>) {
+        let (mut walked, mut stops) = (Vec::new(), Vec::new());
+        let mut stack = vec![root];
+        seen[root] = stamp;
+        while let Some(c) = stack.pop() {
+            if shared[c] && c != root {
+                stops.push(c);
+                continue;
+            }
+            walked.push(c);
+            for &s in &self.components[c].successors {
+                if seen[s] != stamp {
+                    seen[s] = stamp;
+                    stack.push(s);
+                }
+            }
+        }
+        (walked, stops)
+    }
+
+    // the tiles of the walked components together with the collected ones below
+    fn collect(&self, walked: &[usize], stops: &[usize], sets: &[Option<TileSet>]) -> TileSet {
+        let mut tiles = TileSet::new(self.width, self.height);
+        for &c in walked {
+            for &tile in &self.components[c].tiles {
+                tiles.insert(tile);
+            }
+        }
+        for below in stops.iter().filter_map(|&s| sets[s].as_ref()) {
+            tiles.union_with(below);
+        }
+        tiles
+    }
+}
+
+// a walk is done with the shared sets it stopped at, the last one to use a set drops it
+fn release(stops: &[usize], uses: &mut [usize], sets: &mut [Option<TileSet>]) {
+    for &s in stops {
+        uses[s] -= 1;
+        if uses[s] == 0 {
+            sets[s] = None;
+        }
+    }
+}
+
+// Tarjan's algorithm without recursion. Components are numbered in the order they are completed,
+// which puts every component after all the ones it leads to.
+fn strongly_connected(edges: &[Vec<usize>]) -> (Vec<usize>, usize) {
+    const UNVISITED: usize = usize::MAX;
+    let n = edges.len();
+    let mut index = vec![UNVISITED; n];
+    let mut low = vec![0; n];
+    let mut on_stack = vec![false; n];
+    let mut stack = Vec::new();
+    let mut component = vec![0; n];
+    let mut counter = 0;
+    let mut count = 0;
+
+    for root in 0..n {
+        if index[root] != UNVISITED {
+            continue;
+        }
+        let mut calls = vec![(root, 0)];
+        index[root] = counter;
+        low[root] = counter;
+        counter += 1;
+        stack.push(root);
+        on_stack[root] = true;
+
+        while let Some((v, next)) = calls.last_mut() {
+            let v = *v;
+            if let Some(&w) = edges[v].get(*next) {
+                *next += 1;
+                if index[w] == UNVISITED {
+                    index[w] = counter;
+                    low[w] = counter;
+                    counter += 1;
+                    stack.push(w);
+                    on_stack[w] = true;
+                    calls.push((w, 0));
+                } else if on_stack[w] {
+                    low[v] = low[v].min(index[w]);
+                }
+                continue;
+            }
+
+            calls.pop();
+            if let Some(&(u, _)) = calls.last() {
+                low[u] = low[u].min(low[v]);
+            }
+            if low[v] == index[v] {
+                while let Some(w) = stack.pop() {
+                    on_stack[w] = false;
+                    component[w] = count;
+                    if w == v {
+                        break;
+                    }
+                }
+                count += 1;
+            }
+        }
+    }
+    (component, count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::{HashSet, VecDeque};
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    const EXAMPLE: &str = r#"
+        .|...\....
+        |.-.\.....
+        .....|-...
+        ........|.
+        ..........
+        .........\
+        ..../.\\..
+        .-.-/..|..
+        .|....-|.\
+        ..//.|....
+    "#;
+
+    // a breadth-first search over beam states, one per entry point
+    fn count_energized(start_node: Node, contraption: &Contraption) -> usize {
+        let mut visited: HashSet<Node> = HashSet::new();
+        let mut moves: VecDeque<Node> = VecDeque::from([start_node]);
+
+        while let Some(current_node) = moves.pop_front() {
+            visited.insert(current_node);
+            for next_node in contraption.next_nodes(current_node) {
+                if visited.insert(next_node) {
+                    moves.push_back(next_node);
+                }
+            }
+        }
+        visited
+            .into_iter()
+            .map(|(pos, _)| pos)
+            .collect::<HashSet<Pos>>()
+            .len()
+    }
+
+    #[test]
+    fn manual_example() {
+        let contraption: Contraption = EXAMPLE.parse().unwrap();
+        let graph = BeamGraph::new(&contraption);
+        assert_eq!(400, graph.state_count());
+
+        let tiles = graph.energized(((0, 0), Direction::Right));
+        assert_eq!(46, tiles.len());
+        assert!(tiles.contains((0, 0)));
+        assert!(!tiles.contains((9, 9)));
+        assert_eq!(Some((0, 0)), tiles.iter().next());
+
+        let best = graph
+            .energized_from_entries(&contraption)
+            .into_iter()
+            .max_by_key(|(_, tiles)| tiles.len())
+            .unwrap();
+        assert_eq!(((0, 3), Direction::Down), best.0);
+        assert_eq!(51, best.1.len());
+    }
+
+    #[test]
+    fn condensation_is_ordered() {
+        // the beam bounces around the mirrors forever
+        let contraption: Contraption = r#"
+            /.\
+            ...
+            \./
+        "#
+        .parse()
+        .unwrap();
+        let graph = BeamGraph::new(&contraption);
+        let c = graph.component_of(((0, 0), Direction::Up));
+        assert_eq!(c, graph.component_of(((2, 2), Direction::Down)));
+        assert_eq!(8, graph.component_size(c));
+        for c in 0..graph.component_count() {
+            assert!(graph.successors(c).iter().all(|&s| s < c));
+        }
+        assert_eq!(8, graph.energized(((0, 1), Direction::Right)).len());
+    }
+
+    #[test]
+    fn unreachable_loops_are_not_shared() {
+        // the loop between the mirrors is never entered from an edge
+        let contraption: Contraption = r#"
+            /.\
+            ...
+            \./
+        "#
+        .parse()
+        .unwrap();
+        let graph = BeamGraph::new(&contraption);
+        let roots: Vec<usize> = contraption
+            .entry_points()
+            .into_iter()
+            .map(|entry| graph.component_of(entry))
+            .collect();
+        let loop_component = graph.component_of(((0, 0), Direction::Up));
+        assert_eq!(8, graph.component_size(loop_component));
+        assert!(!graph.shared_components(&roots)[loop_component]);
+
+        // entered once, from the left edge of the middle row
+        let contraption: Contraption = r#"
+            /.\
+            |..
+            \./
+        "#
+        .parse()
+        .unwrap();
+        let graph = BeamGraph::new(&contraption);
+        let roots: Vec<usize> = contraption
+            .entry_points()
+            .into_iter()
+            .map(|entry| graph.component_of(entry))
+            .collect();
+        let shared = graph.shared_components(&roots);
+        assert!(shared[graph.component_of(((0, 0), Direction::Up))]);
+        for (entry, tiles) in graph.energized_from_entries(&contraption) {
+            assert_eq!(count_energized(entry, &contraption), tiles.len());
+        }
+    }
+
+    #[test]
+    fn matches_separate_searches() {
+        let mut rng = StdRng::seed_from_u64(16);
+        let text: String = (0..40)
+            .map(|_| {
+                let mut row: String = (0..30)
+                    .map(|_| match rng.gen_range(0..12) {
+                        0 => '/',
+                        1 => '\\',
+                        2 => '-',
+                        3 => '|',
+                        _ => '.',
+                    })
+                    .collect();
+                row.push('\n');
+                row
+            })
+            .collect();
+        let contraption: Contraption = text.parse().unwrap();
+        let graph = BeamGraph::new(&contraption);
+        let all = graph.energized_from_entries(&contraption);
+        assert_eq!(2 * (40 + 30), all.len());
+        for (entry, tiles) in all {
+            assert_eq!(count_energized(entry, &contraption), tiles.len());
+            assert_eq!(graph.energized(entry), tiles);
+        }
+    }
+}
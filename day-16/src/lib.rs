@@ -0,0 +1,172 @@
+use std::str::FromStr;
+
+use anyhow::{bail, Result};
+
+pub mod graph;
+
+// (row, column)
+pub type Pos = (usize, usize);
+// a beam on a tile, moving in a direction
+pub type Node = (Pos, Direction);
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::Left,
+        Direction::Right,
+        Direction::Up,
+        Direction::Down,
+    ];
+
+    pub(crate) fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tile {
+    // `.`
+    Empty,
+    // `/`
+    Mirror,
+    // `\`
+    BackMirror,
+    // `-`
+    HorizontalSplitter,
+    // `|`
+    VerticalSplitter,
+}
+
+impl Tile {
+    // where a beam goes after this tile, one or two directions
+    fn outgoing(&self, dir: Direction) -> &'static [Direction] {
+        use Direction::*;
+        match (self, dir) {
+            (Tile::Empty, Left) => &[Left],
+            (Tile::Empty, Right) => &[Right],
+            (Tile::Empty, Up) => &[Up],
+            (Tile::Empty, Down) => &[Down],
+            (Tile::Mirror, Left) => &[Down],
+            (Tile::Mirror, Right) => &[Up],
+            (Tile::Mirror, Up) => &[Right],
+            (Tile::Mirror, Down) => &[Left],
+            (Tile::BackMirror, Left) => &[Up],
+            (Tile::BackMirror, Right) => &[Down],
+            (Tile::BackMirror, Up) => &[Left],
+            (Tile::BackMirror, Down) => &[Right],
+            (Tile::HorizontalSplitter, Left) => &[Left],
+            (Tile::HorizontalSplitter, Right) => &[Right],
+            (Tile::HorizontalSplitter, Up | Down) => &[Left, Right],
+            (Tile::VerticalSplitter, Left | Right) => &[Up, Down],
+            (Tile::VerticalSplitter, Up) => &[Up],
+            (Tile::VerticalSplitter, Down) => &[Down],
+        }
+    }
+}
+
+impl TryFrom<char> for Tile {
+    type Error = anyhow::Error;
+
+    fn try_from(c: char) -> Result<Self> {
+        match c {
+            '.' => Ok(Tile::Empty),
+            '/' => Ok(Tile::Mirror),
+            '\\' => Ok(Tile::BackMirror),
+            '-' => Ok(Tile::HorizontalSplitter),
+            '|' => Ok(Tile::VerticalSplitter),
+            other => bail!("Unknown tile: {other}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Contraption {
+    width: usize,
+    height: usize,
+    tiles: Vec<Tile>,
+}
+
+impl Contraption {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn tile(&self, (row, col): Pos) -> Tile {
+        self.tiles[row * self.width + col]
+    }
+
+    // the beams leaving a tile, as far as they stay within the contraption
+    pub fn next_nodes(&self, node: Node) -> Vec<Node> {
+        let ((row, col), dir) = node;
+        self.tile((row, col))
+            .outgoing(dir)
+            .iter()
+            .filter_map(|&next_dir| {
+                let pos = match next_dir {
+                    Direction::Left if col > 0 => (row, col - 1),
+                    Direction::Right if col < self.width - 1 => (row, col + 1),
+                    Direction::Up if row > 0 => (row - 1, col),
+                    Direction::Down if row < self.height - 1 => (row + 1, col),
+                    _ => return None,
+                };
+                Some((pos, next_dir))
+            })
+            .collect()
+    }
+
+    // every beam entering from an edge: down along the top row, up along the bottom row, then
+    // right along the left column and left along the right column
+    pub fn entry_points(&self) -> Vec<Node> {
+        let (num_rows, num_cols) = (self.height, self.width);
+        let mut start_nodes = Vec::with_capacity(2 * (num_rows + num_cols));
+        start_nodes.extend((0..num_cols).map(|col| ((0, col), Direction::Down)));
+        start_nodes.extend((0..num_cols).map(|col| ((num_rows - 1, col), Direction::Up)));
+        start_nodes.extend((0..num_rows).map(|row| ((row, 0), Direction::Right)));
+        start_nodes.extend((0..num_rows).map(|row| ((row, num_cols - 1), Direction::Left)));
+        start_nodes
+    }
+}
+
+impl FromStr for Contraption {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let lines: Vec<&str> = s
+            .lines()
+            .map(|l| l.trim())
+            .filter(|l| !l.is_empty())
+            .collect();
+        let height = lines.len();
+        let width = lines.first().map_or(0, |l| l.chars().count());
+        if width == 0 {
+            bail!("Contraption is empty");
+        }
+
+        let mut tiles = Vec::with_capacity(width * height);
+        for line in lines {
+            if line.chars().count() != width {
+                bail!("Contraption rows have different lengths");
+            }
+            for c in line.chars() {
+                tiles.push(Tile::try_from(c)?);
+            }
+        }
+
+        Ok(Self {
+            width,
+            height,
+            tiles,
+        })
+    }
+}